@@ -0,0 +1,107 @@
+use std::fmt;
+
+use crate::{
+    ForthVM, LEN_MASK, MAX_WORD_LEN, input_stream::SourceLocation, mmap,
+    output_stream::out_stream_write,
+};
+
+/// one colon definition on the way to the current instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// name of the enclosing word
+    pub name: String,
+    /// header address of the enclosing word
+    pub header: i32,
+    /// address of the cell that was executing
+    pub addr: i32,
+    /// byte offset of `addr` from the code field address
+    pub offset: i32,
+    /// where the word was defined, if known
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} +{}", self.name, self.offset)?;
+        if let Some(location) = &self.location {
+            write!(f, " ({location})")?;
+        }
+        Ok(())
+    }
+}
+
+impl ForthVM {
+    pub(crate) fn init_backtrace_words(&mut self) {
+        self.register_native("backtrace", |forth| {
+            let frames = forth.backtrace();
+            forth.print_backtrace(&frames);
+        });
+    }
+
+    /// frames of the running words, innermost first.
+    /// the current cell comes first, then the return addresses from RSP down to R0
+    pub fn backtrace(&self) -> Vec<Frame> {
+        let ic = self.read_i32(mmap::IC as i32);
+        let mut frames: Vec<Frame> = self.frame_at(ic - 4).into_iter().collect();
+
+        // rstack() is bottom first
+        for return_addr in self.rstack().into_iter().rev() {
+            // >r values and interpreter temporaries are skipped by frame_at
+            if return_addr % 4 == 0 {
+                frames.extend(self.frame_at(return_addr - 4));
+            }
+        }
+        frames
+    }
+
+    /// backtrace taken at the last error, abort or host function panic
+    pub fn last_backtrace(&self) -> &[Frame] {
+        &self.last_backtrace
+    }
+
+    pub fn print_backtrace(&self, frames: &[Frame]) {
+        let mut s = String::from("\nbacktrace:\n");
+        for (i, frame) in frames.iter().enumerate() {
+            s.push_str(&format!("  #{i} {frame}\n"));
+        }
+        out_stream_write(&s);
+    }
+
+    pub(crate) fn save_backtrace(&mut self) {
+        self.last_backtrace = self.backtrace();
+    }
+
+    /// header of the word whose memory contains `addr`
+    pub fn word_at(&self, addr: i32) -> Option<i32> {
+        if addr < self.dict_start() || addr >= self.here() {
+            return None;
+        }
+
+        // headers are linked from the highest address down
+        let mut idx = self.latest();
+        while idx != 0 {
+            if idx <= addr {
+                return Some(idx);
+            }
+            idx = self.read_i32(idx);
+        }
+        None
+    }
+
+    fn frame_at(&self, addr: i32) -> Option<Frame> {
+        let header = self.word_at(addr)?;
+        let cfa = self.cfa(header);
+        if addr < cfa {
+            return None;
+        }
+
+        let len = (self.read_u8(header + 4) & LEN_MASK) as i32;
+        Some(Frame {
+            name: self.read_string(header + 5, len.min(MAX_WORD_LEN as i32)),
+            header,
+            addr,
+            offset: addr - cfa,
+            location: self.locations.get(&header).cloned(),
+        })
+    }
+}
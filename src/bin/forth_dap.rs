@@ -0,0 +1,518 @@
+//! Debug Adapter Protocol server for forth programs.
+//! speaks DAP over stdin / stdout, output of the program is sent as output events
+
+use std::io::{self, BufRead, Write};
+
+use forthvm::{
+    Breakpoint, ForthError, ForthVM, SourceLocation, StopEvent, StopReason, VmConfig,
+    out_stream_capture, out_stream_take,
+};
+use serde_json::{Value, json};
+
+const THREAD_ID: i64 = 1;
+const PSTACK_REF: i64 = 1;
+const RSTACK_REF: i64 = 2;
+
+/// colon definition found in the program source
+struct Definition {
+    name: String,
+    first_line: usize,
+    last_line: usize,
+}
+
+/// finds `: name ... ;` spans. lines are 1 based
+fn scan_definitions(lines: &[String]) -> Vec<Definition> {
+    let mut definitions = Vec::new();
+    let mut in_definition = false;
+    let mut name: Option<String> = None;
+    let mut first_line = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let line_nr = i + 1;
+        for token in line.split_ascii_whitespace() {
+            if token == "\\" {
+                break;
+            }
+            if !in_definition {
+                if token == ":" {
+                    in_definition = true;
+                    name = None;
+                    first_line = line_nr;
+                }
+            } else if name.is_none() {
+                name = Some(token.to_string());
+            } else if token == ";" {
+                in_definition = false;
+                definitions.push(Definition {
+                    name: name.take().unwrap_or_default(),
+                    first_line,
+                    last_line: line_nr,
+                });
+            }
+        }
+    }
+    definitions
+}
+
+enum Resume {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+}
+
+struct Session {
+    vm: ForthVM,
+    /// runs one `interpret`
+    step_word: usize,
+    path: String,
+    lines: Vec<String>,
+    next_line: usize,
+    definitions: Vec<Definition>,
+    /// words with a breakpoint that are not compiled yet
+    pending: Vec<String>,
+    last_stop: Option<StopEvent>,
+    seq: i64,
+}
+
+impl Session {
+    fn new() -> Self {
+        let conf = VmConfig {
+            memory_size_bytes: 0x10000,
+            max_memory_size_bytes: 0x100000,
+            parameter_stack_size_cells: 256,
+            return_stack_size_cells: 256,
+            call_stack_size_cells: 256,
+            locals_stack_size_cells: 256,
+            heap_size_bytes: 0x1000,
+        };
+        let mut vm = ForthVM::from_config(conf);
+        vm.init_dictionary();
+        let step_word = vm.colon_def("(dap-step)", &["interpret", "bye"]) as usize;
+
+        Session {
+            vm,
+            step_word,
+            path: String::new(),
+            lines: Vec::new(),
+            next_line: 0,
+            definitions: Vec::new(),
+            pending: Vec::new(),
+            last_stop: None,
+            seq: 0,
+        }
+    }
+
+    fn send(&mut self, mut message: Value) {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        let body = message.to_string();
+        let mut out = io::stdout().lock();
+        write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        out.flush().unwrap();
+    }
+
+    fn respond(&mut self, request: &Value, body: Value) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": true,
+            "command": request["command"],
+            "body": body,
+        }));
+    }
+
+    fn respond_error(&mut self, request: &Value, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": false,
+            "command": request["command"],
+            "message": message,
+        }));
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+
+    fn flush_output(&mut self) {
+        let output = out_stream_take();
+        if !output.is_empty() {
+            self.event("output", json!({"category": "stdout", "output": output}));
+        }
+    }
+
+    /// returns false when the session ends
+    fn handle(&mut self, request: &Value) -> bool {
+        let args = &request["arguments"];
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                self.respond(
+                    request,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsEvaluateForHovers": true,
+                    }),
+                );
+                self.event("initialized", json!({}));
+            }
+            "launch" => {
+                let path = args["program"].as_str().unwrap_or_default().to_string();
+                match std::fs::read_to_string(&path) {
+                    Ok(source) => {
+                        self.lines = source.lines().map(str::to_string).collect();
+                        self.definitions = scan_definitions(&self.lines);
+                        self.path = path;
+                        self.respond(request, json!({}));
+                    }
+                    Err(err) => self.respond_error(request, &format!("{path}: {err}")),
+                }
+            }
+            "setBreakpoints" => {
+                let body = self.set_breakpoints(args);
+                self.respond(request, body);
+            }
+            "configurationDone" => {
+                self.respond(request, json!({}));
+                self.resume(Resume::Continue);
+            }
+            "threads" => {
+                self.respond(
+                    request,
+                    json!({"threads": [{"id": THREAD_ID, "name": "forth"}]}),
+                );
+            }
+            "stackTrace" => {
+                let body = self.stack_trace();
+                self.respond(request, body);
+            }
+            "scopes" => {
+                self.respond(
+                    request,
+                    json!({"scopes": [
+                        {"name": "Parameter stack", "variablesReference": PSTACK_REF, "expensive": false},
+                        {"name": "Return stack", "variablesReference": RSTACK_REF, "expensive": false},
+                    ]}),
+                );
+            }
+            "variables" => {
+                let body = self.variables(args["variablesReference"].as_i64().unwrap_or(0));
+                self.respond(request, body);
+            }
+            "continue" => {
+                self.respond(request, json!({"allThreadsContinued": true}));
+                self.resume(Resume::Continue);
+            }
+            "next" => {
+                self.respond(request, json!({}));
+                self.resume(Resume::StepOver);
+            }
+            "stepIn" => {
+                self.respond(request, json!({}));
+                self.resume(Resume::StepIn);
+            }
+            "stepOut" => {
+                self.respond(request, json!({}));
+                self.resume(Resume::StepOut);
+            }
+            "evaluate" => {
+                let expression = args["expression"].as_str().unwrap_or_default();
+                match self.evaluate(expression) {
+                    Ok(result) => {
+                        self.respond(request, json!({"result": result, "variablesReference": 0}))
+                    }
+                    Err(err) => self.respond_error(request, &err.to_string()),
+                }
+                self.flush_output();
+            }
+            "disconnect" => {
+                self.respond(request, json!({}));
+                return false;
+            }
+            _ => self.respond(request, json!({})),
+        }
+        true
+    }
+
+    /// breakpoints inside a colon definition stop when the word is entered.
+    /// lines outside of definitions can't be mapped to a word
+    fn set_breakpoints(&mut self, args: &Value) -> Value {
+        self.pending.clear();
+        self.vm.clear_breakpoints();
+
+        let mut breakpoints = Vec::new();
+        let requested = args["breakpoints"].as_array().cloned().unwrap_or_default();
+        for bp in requested {
+            let line = bp["line"].as_u64().unwrap_or(0) as usize;
+            let word = self
+                .definitions
+                .iter()
+                .find(|d| d.first_line <= line && line <= d.last_line);
+
+            match word {
+                Some(definition) => {
+                    self.pending.push(definition.name.clone());
+                    breakpoints.push(json!({"verified": true, "line": definition.first_line}));
+                }
+                None => breakpoints.push(json!({"verified": false, "line": line})),
+            }
+        }
+        self.activate_breakpoints();
+
+        json!({"breakpoints": breakpoints})
+    }
+
+    /// words only exist after they were compiled
+    fn activate_breakpoints(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        for name in pending {
+            if self
+                .vm
+                .add_breakpoint(Breakpoint::Word(name.clone()))
+                .is_err()
+            {
+                self.pending.push(name);
+            }
+        }
+    }
+
+    fn resume(&mut self, how: Resume) {
+        let mut result = if self.vm.is_paused() {
+            Some(match how {
+                Resume::Continue => self.vm.debug_continue(),
+                Resume::StepIn => self.vm.step_into(),
+                Resume::StepOver => self.vm.step_over(),
+                Resume::StepOut => self.vm.step_out(),
+            })
+        } else {
+            self.run_next()
+        };
+
+        loop {
+            self.flush_output();
+            match result {
+                None => {
+                    self.event("terminated", json!({}));
+                    return;
+                }
+                Some(Ok(event)) if event.reason != StopReason::Finished => {
+                    let reason = match event.reason {
+                        StopReason::Breakpoint => "breakpoint",
+                        StopReason::Watchpoint { .. } => "data breakpoint",
+                        _ => "step",
+                    };
+                    self.last_stop = Some(event);
+                    self.event(
+                        "stopped",
+                        json!({"reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true}),
+                    );
+                    return;
+                }
+                Some(Ok(_)) => {}
+                Some(Err(err)) => {
+                    self.event(
+                        "output",
+                        json!({"category": "stderr", "output": format!("{err}\n")}),
+                    );
+                    self.vm.abort();
+                }
+            }
+            result = self.run_next();
+        }
+    }
+
+    /// interprets the next word of the program. None at the end of the program
+    fn run_next(&mut self) -> Option<Result<StopEvent, ForthError>> {
+        while !self.vm.input_pending() {
+            let line = self.lines.get(self.next_line)?;
+            let line = format!("{line}\n");
+            self.vm.fill_input_buffer(&line);
+            self.next_line += 1;
+            self.vm.set_source_location(&self.path, self.next_line);
+        }
+        self.activate_breakpoints();
+        Some(self.vm.debug_word(self.step_word))
+    }
+
+    /// the word about to run, then its callers from the backtrace. lines are
+    /// those of the definitions, positions inside a word are not tracked
+    fn stack_trace(&self) -> Value {
+        let Some(stop) = self.last_stop.as_ref() else {
+            return json!({"stackFrames": [], "totalFrames": 0});
+        };
+
+        let label = format!("{} (ic {})", stop.name, stop.ic);
+        let mut frames = vec![self.frame(0, &label, &stop.name, None)];
+        for (i, frame) in self.vm.backtrace().iter().enumerate() {
+            let label = format!("{} +{}", frame.name, frame.offset);
+            frames.push(self.frame(i + 1, &label, &frame.name, frame.location.as_ref()));
+        }
+        json!({"stackFrames": frames, "totalFrames": frames.len()})
+    }
+
+    /// words without a known definition, like the interpreter, get no source
+    fn frame(
+        &self,
+        id: usize,
+        label: &str,
+        word: &str,
+        location: Option<&SourceLocation>,
+    ) -> Value {
+        let source = match location {
+            Some(location) => Some((location.file.as_str(), location.line)),
+            None => self
+                .definitions
+                .iter()
+                .find(|d| d.name == word)
+                .map(|d| (self.path.as_str(), d.first_line)),
+        };
+        match source {
+            Some((path, line)) => json!({
+                "id": id,
+                "name": label,
+                "source": {"path": path},
+                "line": line,
+                "column": 1,
+            }),
+            None => json!({
+                "id": id,
+                "name": label,
+                "line": 0,
+                "column": 0,
+                "presentationHint": "subtle",
+            }),
+        }
+    }
+
+    fn variables(&self, reference: i64) -> Value {
+        let Some(stop) = self.last_stop.as_ref() else {
+            return json!({"variables": []});
+        };
+        let stack = match reference {
+            PSTACK_REF => &stop.pstack,
+            RSTACK_REF => &stop.rstack,
+            _ => return json!({"variables": []}),
+        };
+
+        // top of stack first
+        let variables: Vec<Value> = stack
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, value)| {
+                json!({"name": format!("{i}"), "value": value.to_string(), "variablesReference": 0})
+            })
+            .collect();
+        json!({"variables": variables})
+    }
+
+    /// interprets `expression` without disturbing the paused program.
+    /// returns the values it left on the stack
+    fn evaluate(&mut self, expression: &str) -> Result<String, ForthError> {
+        let values = self.vm.debug_evaluate(expression)?;
+        Ok(values
+            .iter()
+            .map(i32::to_string)
+            .collect::<Vec<_>>()
+            .join(" "))
+    }
+}
+
+fn read_message(input: &mut impl BufRead) -> Option<Value> {
+    let mut len = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            len = value.trim().parse().ok();
+        }
+    }
+
+    let mut body = vec![0; len?];
+    input.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+fn main() {
+    out_stream_capture();
+    let mut session = Session::new();
+    let mut input = io::stdin().lock();
+
+    while let Some(request) = read_message(&mut input) {
+        if request["type"] == "request" && !session.handle(&request) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(source: &str) -> Vec<String> {
+        source.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_read_message() {
+        let first = r#"{"seq":1,"type":"request","command":"initialize"}"#;
+        let second = r#"{"seq":2,"type":"request","command":"threads"}"#;
+        let stream = format!(
+            "Content-Length: {}\r\n\r\n{first}Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{second}",
+            first.len(),
+            second.len()
+        );
+        let mut input = io::Cursor::new(stream);
+
+        let message = read_message(&mut input).unwrap();
+        assert_eq!(message["command"], "initialize");
+        let message = read_message(&mut input).unwrap();
+        assert_eq!(message["seq"], 2);
+        assert!(read_message(&mut input).is_none(), "end of input");
+    }
+
+    #[test]
+    fn test_read_message_errors() {
+        // no Content-Length header
+        let mut input = io::Cursor::new("\r\n{}");
+        assert!(read_message(&mut input).is_none());
+
+        // body shorter than announced
+        let mut input = io::Cursor::new("Content-Length: 10\r\n\r\n{}");
+        assert!(read_message(&mut input).is_none());
+    }
+
+    #[test]
+    fn test_scan_definitions() {
+        let source = lines(
+            ": square dup * ;\n\
+             \\ : commented out ;\n\
+             1 2 + .\n\
+             : cube\n\
+             \x20 dup square * ; : one 1 ;\n",
+        );
+        let found: Vec<_> = scan_definitions(&source)
+            .into_iter()
+            .map(|d| (d.name, d.first_line, d.last_line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("square".to_string(), 1, 1),
+                ("cube".to_string(), 4, 5),
+                ("one".to_string(), 5, 5),
+            ]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use toyvm::{VM, opcode};
+
+use crate::{
+    CELL, FALSE, ForthVM, TRUE,
+    error::{ForthError, Result},
+    forthvm::NEXT,
+    init_dictionary::_word,
+};
+
+/// channel layout. a ring buffer of `CAPACITY` cells
+pub mod chan {
+    pub const CAPACITY: i32 = 0;
+    /// number of queued values
+    pub const COUNT: i32 = 4;
+    /// index of the oldest value
+    pub const HEAD: i32 = 8;
+    pub const BUF: i32 = 12;
+}
+
+impl ForthVM {
+    pub(crate) fn init_channel_words(&mut self) {
+        self.vm_call("try-send", &try_send);
+        self.vm_call("try-receive", &try_receive);
+
+        // ( x ch -- ) waits with pause while the channel is full
+        self.colon_def(
+            "send",
+            &[
+                "2dup",     // 0
+                "try-send", // 1
+                "0branch",  // 2
+                "12",       // 3 (6 - 3) * 4
+                "2drop",    // 4
+                "exit",     // 5
+                "pause",    // 6
+                "branch",   // 7
+                "-32",      // 8 (0 - 8) * 4
+            ],
+        );
+
+        // ( ch -- x ) waits with pause while the channel is empty
+        self.colon_def(
+            "receive",
+            &[
+                "dup",         // 0
+                "try-receive", // 1
+                "0branch",     // 2
+                "12",          // 3 (6 - 3) * 4
+                "nip",         // 4
+                "exit",        // 5
+                "pause",       // 6
+                "branch",      // 7
+                "-32",         // 8 (0 - 8) * 4
+            ],
+        );
+
+        // ( capacity "name" -- )
+        self.register_native("channel", |forth| {
+            let capacity = forth.pop_i32();
+            let (len, ptr) = _word(&mut forth.vm);
+            let name = forth.read_string(ptr, len);
+            if let Err(err) = forth.new_channel(&name, capacity) {
+                forth.report_error(&format!("channel: {err}"));
+            }
+        });
+    }
+
+    /// creates a channel holding up to `capacity` values and a word `name`
+    /// that pushes its address. returns the channel address
+    pub fn new_channel(&mut self, name: &str, capacity: i32) -> Result<i32> {
+        if capacity <= 0 {
+            return Err(ForthError::InvalidCapacity(capacity));
+        }
+        let ch = self.allot(chan::BUF + capacity * CELL)?;
+        self.write_i32(capacity, ch + chan::CAPACITY);
+        self.write_i32(0, ch + chan::COUNT);
+        self.write_i32(0, ch + chan::HEAD);
+        self.channels.insert(ch);
+
+        let c = ch.to_ne_bytes();
+        self.builtin(name, &[opcode::I32_CONST, c[0], c[1], c[2], c[3], NEXT]);
+
+        Ok(ch)
+    }
+
+    /// puts `value` into channel `name`. returns false if the channel is full
+    pub fn channel_send(&mut self, name: &str, value: i32) -> Result<bool> {
+        let ch = self.channel_addr(name)?;
+        Ok(_try_send(&mut self.vm, value, ch))
+    }
+
+    /// takes all queued values out of channel `name`, oldest first
+    pub fn channel_drain(&mut self, name: &str) -> Result<Vec<i32>> {
+        let ch = self.channel_addr(name)?;
+        let mut values = Vec::new();
+        while let Some(value) = _try_receive(&mut self.vm, ch) {
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    /// address pushed by the channel word `name`, without running it.
+    /// only addresses made by `new_channel` are accepted
+    fn channel_addr(&self, name: &str) -> Result<i32> {
+        let header = self
+            .find(name)
+            .ok_or_else(|| ForthError::UndefinedWord(name.to_string()))?;
+        let code = self.cfa(header) + 4;
+        let is_const = self.read_u8(code) == opcode::I32_CONST && self.read_u8(code + 5) == NEXT;
+        let ch = self.read_i32(code + 1);
+        if !is_const || !self.channels.contains(&ch) {
+            return Err(ForthError::InvalidChannel(name.to_string()));
+        }
+        Ok(ch)
+    }
+}
+
+// ( x ch -- flag )
+fn try_send(vm: &mut VM) {
+    let ch = vm.pop_i32();
+    let value = vm.pop_i32();
+    let sent = _try_send(vm, value, ch);
+    vm.push_i32(if sent { TRUE } else { FALSE });
+}
+
+fn _try_send(vm: &mut VM, value: i32, ch: i32) -> bool {
+    let ch = ch as usize;
+    let capacity = vm.read_i32(ch + chan::CAPACITY as usize);
+    let count = vm.read_i32(ch + chan::COUNT as usize);
+    if count == capacity {
+        return false;
+    }
+
+    let head = vm.read_i32(ch + chan::HEAD as usize);
+    let tail = (head + count) % capacity;
+    vm.write_i32(value, ch + (chan::BUF + tail * CELL) as usize);
+    vm.write_i32(count + 1, ch + chan::COUNT as usize);
+    true
+}
+
+// ( ch -- x true | false )
+fn try_receive(vm: &mut VM) {
+    let ch = vm.pop_i32();
+    if let Some(value) = _try_receive(vm, ch) {
+        vm.push_i32(value);
+        vm.push_i32(TRUE);
+    } else {
+        vm.push_i32(FALSE);
+    }
+}
+
+fn _try_receive(vm: &mut VM, ch: i32) -> Option<i32> {
+    let ch = ch as usize;
+    let count = vm.read_i32(ch + chan::COUNT as usize);
+    if count == 0 {
+        return None;
+    }
+
+    let capacity = vm.read_i32(ch + chan::CAPACITY as usize);
+    let head = vm.read_i32(ch + chan::HEAD as usize);
+    let value = vm.read_i32(ch + (chan::BUF + head * CELL) as usize);
+    vm.write_i32((head + 1) % capacity, ch + chan::HEAD as usize);
+    vm.write_i32(count - 1, ch + chan::COUNT as usize);
+    Some(value)
+}
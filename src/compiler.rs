@@ -0,0 +1,116 @@
+use crate::{
+    COMPILE_ONLY, COMPILING, ForthVM, IMMEDIATE, init_dictionary::_word, mmap,
+    output_stream::out_stream_write,
+};
+
+impl ForthVM {
+    pub(crate) fn init_compiler_words(&mut self) {
+        // ( "name" -- xt ) an undefined name is reported and the rest of the line skipped
+        self.register_native("'", |forth| match forth.parse_word() {
+            Some(word) => forth.push_i32(forth.cfa(word)),
+            None => {
+                let len = forth.read_i32(mmap::SOURCE_LEN as i32);
+                forth.write_i32(len, mmap::TO_IN as i32);
+            }
+        });
+        self.colon_def("compile,", &[",", "exit"]);
+
+        self.register_native("compile-only", |forth| {
+            let flags = forth.latest() + 4;
+            forth.write_u8(forth.read_u8(flags) | COMPILE_ONLY, flags);
+        });
+
+        let lit = self.xt_of("lit");
+        let comma = self.xt_of(",");
+
+        // ( "name" -- ) compiles the compilation semantics of name
+        self.compiler_word("postpone", move |forth| {
+            let Some(word) = forth.parse_word() else {
+                return;
+            };
+            let xt = forth.cfa(word);
+            if forth.read_u8(word + 4) & IMMEDIATE != 0 {
+                forth.compile_cell(xt);
+            } else {
+                forth.compile_cell(lit);
+                forth.compile_cell(xt);
+                forth.compile_cell(comma);
+            }
+        });
+        // ( "name" -- ) compiles xt of name as literal
+        self.compiler_word("[']", move |forth| {
+            if let Some(word) = forth.parse_word() {
+                let xt = forth.cfa(word);
+                forth.compile_literal(lit, xt);
+            }
+        });
+        // ( "name" -- ) compiles first char of name as literal
+        self.compiler_word("[char]", move |forth| {
+            let (_, ptr) = _word(&mut forth.vm);
+            let c = forth.read_u8(ptr) as i32;
+            forth.compile_literal(lit, c);
+        });
+        // ( x -- )
+        self.compiler_word("literal", move |forth| {
+            let x = forth.pop_i32();
+            forth.compile_literal(lit, x);
+        });
+        // ( x1 x2 -- )
+        self.compiler_word("2literal", move |forth| {
+            let x2 = forth.pop_i32();
+            let x1 = forth.pop_i32();
+            forth.compile_literal(lit, x1);
+            forth.compile_literal(lit, x2);
+        });
+        // calls the word being defined
+        self.compiler_word("recurse", |forth| {
+            let xt = forth.cfa(forth.latest());
+            forth.compile_cell(xt);
+        });
+
+        // ( -- xt ) starts compiling a word without name, ended by `;`.
+        // `;` toggles the hidden flag, so the header ends up hidden
+        self.register_native(":noname", |forth| {
+            let header = forth.here();
+            forth.write_previous_idx();
+            forth.write_name("", 0);
+            forth.set_latest(header);
+            forth.write_docol_addr();
+            forth.set_state(COMPILING);
+
+            let xt = forth.cfa(header);
+            forth.push_i32(xt);
+        });
+    }
+
+    /// prints `msg` with the current source location
+    pub(crate) fn report_error(&self, msg: &str) {
+        out_stream_write(&format!("\n{}: {msg}\n", self.source_location()));
+    }
+
+    /// immediate and compile-only native word
+    fn compiler_word<F>(&mut self, name: &str, f: F)
+    where
+        F: FnMut(&mut ForthVM) + 'static,
+    {
+        self.register_native(name, f);
+        let flags = self.latest() + 4;
+        self.write_u8(self.read_u8(flags) | IMMEDIATE | COMPILE_ONLY, flags);
+    }
+
+    /// header of the next word in the input, reports undefined names
+    fn parse_word(&mut self) -> Option<i32> {
+        let (len, ptr) = _word(&mut self.vm);
+        let name = self.read_string(ptr, len);
+        let word = self.find(&name);
+        if word.is_none() {
+            self.report_error(&format!("undefined word '{name}'"));
+        }
+        word
+    }
+
+    fn compile_literal(&mut self, lit: i32, x: i32) {
+        self.compile_cell(lit);
+        self.compile_cell(x);
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{
+    ForthVM, LEN_MASK, MAX_WORD_LEN, align, input_stream::SourceLocation, mmap,
+    output_stream::out_stream_write,
+};
+
+/// executed cells of one colon definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordCoverage {
+    pub name: String,
+    pub header: i32,
+    /// threaded cells, operands of `lit`, `branch` .. not included
+    pub cells: usize,
+    pub executed: usize,
+    pub location: Option<SourceLocation>,
+}
+
+/// `branch` or `0branch` inside a colon definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchCoverage {
+    pub word: String,
+    /// byte offset of the branch cell from the code field address
+    pub offset: i32,
+    /// byte offset of the branch target from the code field address
+    pub target_offset: i32,
+    /// NEXT dispatched the target right after the branch
+    pub taken: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub words: Vec<WordCoverage>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+impl CoverageReport {
+    pub fn unexecuted_words(&self) -> Vec<&WordCoverage> {
+        self.words.iter().filter(|w| w.executed == 0).collect()
+    }
+
+    pub fn untaken_branches(&self) -> Vec<&BranchCoverage> {
+        self.branches.iter().filter(|b| !b.taken).collect()
+    }
+
+    pub fn to_text(&self) -> String {
+        let executed = self.words.len() - self.unexecuted_words().len();
+        let cells: usize = self.words.iter().map(|w| w.cells).sum();
+        let cells_hit: usize = self.words.iter().map(|w| w.executed).sum();
+        let taken = self.branches.len() - self.untaken_branches().len();
+
+        let mut s = format!(
+            "words {executed}/{}, cells {cells_hit}/{cells}, branches {taken}/{}\n",
+            self.words.len(),
+            self.branches.len()
+        );
+        for word in self.unexecuted_words() {
+            s += &format!("never executed: {}", word.name);
+            if let Some(location) = &word.location {
+                s += &format!(" ({location})");
+            }
+            s.push('\n');
+        }
+        for branch in self.untaken_branches() {
+            s += &format!(
+                "never taken: {} +{} -> +{}\n",
+                branch.word, branch.offset, branch.target_offset
+            );
+        }
+        s
+    }
+
+    /// lcov tracefile. words without source location are listed under `<rust>`,
+    /// line numbers are the definition lines
+    pub fn to_lcov(&self) -> String {
+        let mut files: BTreeMap<&str, Vec<&WordCoverage>> = BTreeMap::new();
+        for word in &self.words {
+            let file = word.location.as_ref().map_or("<rust>", |l| l.file.as_str());
+            files.entry(file).or_default().push(word);
+        }
+
+        let mut s = String::new();
+        for (file, words) in files {
+            s += &format!("TN:\nSF:{file}\n");
+            for word in &words {
+                let line = word.location.as_ref().map_or(0, |l| l.line);
+                s += &format!("FN:{line},{}\n", word.name);
+            }
+            for word in &words {
+                s += &format!("FNDA:{},{}\n", (word.executed > 0) as u8, word.name);
+            }
+            let hit = words.iter().filter(|w| w.executed > 0).count();
+            s += &format!("FNF:{}\nFNH:{hit}\n", words.len());
+
+            let mut branches = 0;
+            let mut branches_hit = 0;
+            for word in &words {
+                let line = word.location.as_ref().map_or(0, |l| l.line);
+                let word_branches = self.branches.iter().filter(|b| b.word == word.name);
+                for (i, branch) in word_branches.enumerate() {
+                    let taken = if word.executed == 0 {
+                        "-".to_string()
+                    } else {
+                        (branch.taken as u8).to_string()
+                    };
+                    s += &format!("BRDA:{line},0,{i},{taken}\n");
+                    branches += 1;
+                    branches_hit += branch.taken as usize;
+                }
+            }
+            s += &format!("BRF:{branches}\nBRH:{branches_hit}\nend_of_record\n");
+        }
+        s
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Coverage {
+    /// cells dispatched by NEXT
+    executed: HashSet<i32>,
+    /// branch cells whose target was dispatched next
+    taken: HashSet<i32>,
+    /// branch cell and its target, when the last dispatch was a branch
+    last_branch: Option<(i32, i32)>,
+    branch_xts: [i32; 2],
+}
+
+impl ForthVM {
+    pub(crate) fn init_coverage_words(&mut self) {
+        self.register_native(".coverage", |forth| {
+            out_stream_write(&forth.coverage_report().to_text());
+        });
+    }
+
+    /// starts recording executed cells. clears earlier results
+    pub fn start_coverage(&mut self) {
+        let branch_xts = ["branch", "0branch"].map(|name| self.xt_of(name));
+        self.coverage = Some(Coverage {
+            branch_xts,
+            ..Default::default()
+        });
+    }
+
+    /// stops recording and returns the results
+    pub fn stop_coverage(&mut self) -> CoverageReport {
+        let report = self.coverage_report();
+        self.coverage = None;
+        report
+    }
+
+    pub fn is_covering(&self) -> bool {
+        self.coverage.is_some()
+    }
+
+    /// coverage of all colon definitions in the dictionary, oldest first
+    pub fn coverage_report(&self) -> CoverageReport {
+        let Some(coverage) = self.coverage.as_ref() else {
+            return CoverageReport::default();
+        };
+
+        let [lit, lit_add, lit_fetch, tail, litstring] =
+            ["lit", "(lit+)", "(lit@)", "(tail)", "litstring"].map(|name| self.xt_of(name));
+        let mut headers = Vec::new();
+        let mut idx = self.latest();
+        while idx != 0 {
+            headers.push(idx);
+            idx = self.read_i32(idx);
+        }
+        let xts: HashSet<i32> = headers.iter().map(|&h| self.cfa(h)).collect();
+
+        // collected newest first, one entry per colon definition
+        let mut words = Vec::new();
+        let mut end = self.here();
+        for &header in &headers {
+            let cfa = self.cfa(header);
+            if self.read_i32(cfa) == mmap::DOCOL as i32 {
+                let len = (self.read_u8(header + 4) & LEN_MASK) as i32;
+                let name = self.read_string(header + 5, len.min(MAX_WORD_LEN as i32));
+                let mut word = WordCoverage {
+                    name: name.clone(),
+                    header,
+                    cells: 0,
+                    executed: 0,
+                    location: self.locations.get(&header).cloned(),
+                };
+
+                let mut branches = Vec::new();
+                let mut cell = cfa + 4;
+                // cells after the last word are data, not code
+                while cell < end && xts.contains(&self.read_i32(cell)) {
+                    let xt = self.read_i32(cell);
+                    word.cells += 1;
+                    word.executed += coverage.executed.contains(&cell) as usize;
+
+                    if coverage.branch_xts.contains(&xt) {
+                        let target = cell + 4 + self.read_i32(cell + 4);
+                        branches.push(BranchCoverage {
+                            word: name.clone(),
+                            offset: cell - cfa,
+                            target_offset: target - cfa,
+                            taken: coverage.taken.contains(&cell),
+                        });
+                        cell += 8;
+                    } else if [lit, lit_add, lit_fetch, tail].contains(&xt) {
+                        cell += 8;
+                    } else if xt == litstring {
+                        cell = align(cell + 8 + self.read_i32(cell + 4));
+                    } else {
+                        cell += 4;
+                    }
+                }
+                words.push((word, branches));
+            }
+            end = header;
+        }
+
+        let mut report = CoverageReport::default();
+        for (word, branches) in words.into_iter().rev() {
+            report.words.push(word);
+            report.branches.extend(branches);
+        }
+        report
+    }
+
+    pub(crate) fn coverage_next(&mut self) {
+        if self.coverage.is_none() {
+            return;
+        }
+
+        let ic = self.read_i32(mmap::IC as i32);
+        let xt = self.read_i32(ic);
+        let offset = self.read_i32(ic + 4);
+        let Some(coverage) = self.coverage.as_mut() else {
+            return;
+        };
+
+        if let Some((branch, target)) = coverage.last_branch.take()
+            && ic == target
+        {
+            coverage.taken.insert(branch);
+        }
+        coverage.executed.insert(ic);
+        if coverage.branch_xts.contains(&xt) {
+            coverage.last_branch = Some((ic, ic + 4 + offset));
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ForthVM, RunStatus,
+    error::{ForthError, Result},
+    mmap,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Breakpoint {
+    /// stop before the word is executed
+    Word(String),
+    /// stop before the word at this code field address is executed
+    Xt(i32),
+    /// stop when IC reaches this cell of a colon definition
+    Address(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason {
+    Breakpoint,
+    Step,
+    /// watched memory cell changed
+    Watchpoint {
+        addr: i32,
+        old: i32,
+        new: i32,
+    },
+    /// the word ran to `bye`
+    Finished,
+}
+
+/// where and why the debugger stopped.
+/// `name`, `xt` and `ic` describe the word that runs next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StopEvent {
+    pub reason: StopReason,
+    pub name: String,
+    pub xt: i32,
+    pub ic: i32,
+    /// parameter stack, bottom first
+    pub pstack: Vec<i32>,
+    /// return stack, bottom first
+    pub rstack: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum StepMode {
+    #[default]
+    Run,
+    Into,
+    /// stop at return stack depth <= n
+    Over(i32),
+    /// stop at return stack depth < n
+    Out(i32),
+}
+
+/// breakpoints and watchpoints. only checked by the debug_* and step_* runs
+#[derive(Default)]
+pub struct Debugger {
+    xts: HashSet<i32>,
+    addresses: HashSet<i32>,
+    /// address -> last seen value
+    watchpoints: HashMap<i32, i32>,
+    mode: StepMode,
+    attached: bool,
+    /// don't stop again at the dispatch we stopped at
+    skip_once: bool,
+    stop_reason: Option<StopReason>,
+}
+
+impl ForthVM {
+    pub fn add_breakpoint(&mut self, bp: Breakpoint) -> Result<()> {
+        match bp {
+            Breakpoint::Word(name) => {
+                let xt = self.checked_xt(&name)?;
+                self.debugger.xts.insert(xt);
+            }
+            Breakpoint::Xt(xt) => {
+                self.debugger.xts.insert(xt);
+            }
+            Breakpoint::Address(ic) => {
+                self.debugger.addresses.insert(ic);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_breakpoint(&mut self, bp: Breakpoint) -> Result<()> {
+        match bp {
+            Breakpoint::Word(name) => {
+                let xt = self.checked_xt(&name)?;
+                self.debugger.xts.remove(&xt);
+            }
+            Breakpoint::Xt(xt) => {
+                self.debugger.xts.remove(&xt);
+            }
+            Breakpoint::Address(ic) => {
+                self.debugger.addresses.remove(&ic);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.debugger.xts.clear();
+        self.debugger.addresses.clear();
+    }
+
+    /// stop when the cell at `addr` changes
+    pub fn add_watchpoint(&mut self, addr: i32) {
+        let value = self.read_i32(addr);
+        self.debugger.watchpoints.insert(addr, value);
+    }
+
+    pub fn remove_watchpoint(&mut self, addr: i32) {
+        self.debugger.watchpoints.remove(&addr);
+    }
+
+    /// runs word until a breakpoint or watchpoint is hit or it finishes
+    pub fn debug_word(&mut self, word_idx: usize) -> Result<StopEvent> {
+        let ip = self.cold_start(word_idx);
+        self.debugger.mode = StepMode::Run;
+        self.debugger.skip_once = false;
+        self.debug_run(ip)
+    }
+
+    /// continues a stopped run until the next breakpoint
+    pub fn debug_continue(&mut self) -> Result<StopEvent> {
+        self.debug_resume(StepMode::Run)
+    }
+
+    /// runs the next word and stops at the first word it executes
+    pub fn step_into(&mut self) -> Result<StopEvent> {
+        self.debug_resume(StepMode::Into)
+    }
+
+    /// runs the next word including everything it calls
+    pub fn step_over(&mut self) -> Result<StopEvent> {
+        let depth = self.rstack_depth();
+        self.debug_resume(StepMode::Over(depth))
+    }
+
+    /// runs until the current colon definition returns
+    pub fn step_out(&mut self) -> Result<StopEvent> {
+        let depth = self.rstack_depth();
+        self.debug_resume(StepMode::Out(depth))
+    }
+
+    fn debug_resume(&mut self, mode: StepMode) -> Result<StopEvent> {
+        let ip = self.paused_ip.take().ok_or(ForthError::NotPaused)?;
+        self.debugger.mode = mode;
+        self.debugger.skip_once = true;
+        self.debug_run(ip)
+    }
+
+    fn debug_run(&mut self, ip: usize) -> Result<StopEvent> {
+        self.debugger.attached = true;
+        let status = self.run_checked(ip, None);
+        self.debugger.attached = false;
+
+        let reason = match status? {
+            RunStatus::Stopped => self.debugger.stop_reason.take().unwrap_or(StopReason::Step),
+            _ => StopReason::Finished,
+        };
+        Ok(self.stop_event(reason))
+    }
+
+    fn stop_event(&self, reason: StopReason) -> StopEvent {
+        let ic = self.read_i32(mmap::IC as i32);
+        let xt = self.read_i32(ic);
+        StopEvent {
+            reason,
+            name: self.xt_name(xt),
+            xt,
+            ic,
+            pstack: self.pstack(),
+            rstack: self.rstack(),
+        }
+    }
+
+    /// interprets `source` while a run is stopped and returns the values it
+    /// left on the stack. when `source` fails the stopped run is put back as it was
+    pub fn debug_evaluate(&mut self, source: &str) -> Result<Vec<i32>> {
+        const SAVED: [usize; 13] = [
+            mmap::DSP,
+            mmap::S0,
+            mmap::RSP,
+            mmap::R0,
+            mmap::TASK,
+            mmap::IC,
+            mmap::A0,
+            mmap::STATE,
+            mmap::LATEST,
+            mmap::SOURCE_ADDR,
+            mmap::SOURCE_LEN,
+            mmap::TO_IN,
+            mmap::SOURCE_ID,
+        ];
+        let saved = SAVED.map(|cell| self.read_i32(cell as i32));
+        let here = self.here();
+        let paused_ip = self.paused_ip;
+        let depth = self.pstack_depth();
+
+        if let Err(err) = self.evaluate(source) {
+            for (cell, value) in SAVED.into_iter().zip(saved) {
+                self.write_i32(value, cell as i32);
+            }
+            self.rewind_here(here);
+            self.paused_ip = paused_ip;
+            return Err(err);
+        }
+
+        let mut values = Vec::new();
+        while self.pstack_depth() > depth {
+            values.push(self.pop_i32());
+        }
+        values.reverse();
+        Ok(values)
+    }
+
+    /// keeps nested runs (`call` from a host function) from stopping
+    pub(crate) fn detach_debugger(&mut self) -> bool {
+        std::mem::replace(&mut self.debugger.attached, false)
+    }
+
+    pub(crate) fn attach_debugger(&mut self, attached: bool) {
+        self.debugger.attached = attached;
+    }
+
+    /// called before every NEXT dispatch. returns true if the run should stop
+    pub(crate) fn debug_check(&mut self) -> bool {
+        if !self.debugger.attached {
+            return false;
+        }
+
+        let ic = self.read_i32(mmap::IC as i32);
+        let xt = self.read_i32(ic);
+
+        let mut reason = None;
+        for (addr, old) in self.debugger.watchpoints.iter_mut() {
+            let new = self.vm.read_i32(*addr as usize);
+            if new != *old {
+                if reason.is_none() {
+                    reason = Some(StopReason::Watchpoint {
+                        addr: *addr,
+                        old: *old,
+                        new,
+                    });
+                }
+                *old = new;
+            }
+        }
+
+        // breakpoints and steps are not checked right after resuming or when
+        // returning to rust, changed watchpoints are still reported
+        let skip = std::mem::take(&mut self.debugger.skip_once) || xt == mmap::HALT_XT as i32;
+        if reason.is_none() && !skip {
+            let depth = self.rstack_depth();
+            let step = match self.debugger.mode {
+                StepMode::Run => false,
+                StepMode::Into => true,
+                StepMode::Over(d) => depth <= d,
+                StepMode::Out(d) => depth < d,
+            };
+            if step {
+                reason = Some(StopReason::Step);
+            } else if self.debugger.xts.contains(&xt) || self.debugger.addresses.contains(&ic) {
+                reason = Some(StopReason::Breakpoint);
+            }
+        }
+
+        if reason.is_some() {
+            self.debugger.mode = StepMode::Run;
+            self.debugger.stop_reason = reason;
+            return true;
+        }
+        false
+    }
+
+    fn checked_xt(&self, name: &str) -> Result<i32> {
+        self.find(name)
+            .map(|w| self.cfa(w))
+            .ok_or_else(|| ForthError::UndefinedWord(name.to_string()))
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForthError {
+    /// toyvm reported an error while executing bytecode
+    Vm(String),
+    /// run was stopped through an `InterruptHandle`
+    Interrupted,
+    /// host function was called again while it was still running
+    HostFnBusy(usize),
+    /// host function panicked, holds the panic message
+    HostPanic(String),
+    /// name could not be found in the dictionary
+    UndefinedWord(String),
+    /// word consumed more values than it was given
+    StackUnderflow,
+    /// word left another number of results than `call_checked` expected
+    StackEffect { expected: usize, actual: usize },
+    /// channels need room for at least one value
+    InvalidCapacity(i32),
+    /// the word does not push a channel made by `new_channel` or `channel`
+    InvalidChannel(String),
+    /// there is no stopped run to continue
+    NotPaused,
+    /// file could not be read
+    Io(String),
+    /// source passed to `evaluate` does not fit its buffer
+    SourceTooLong(usize),
+    /// the heap has no free block of the requested size
+    OutOfMemory(usize),
+    /// address was not returned by `allocate`
+    InvalidAddress(i32),
+    /// the dictionary reached `VmConfig::max_memory_size_bytes`
+    DictionaryFull,
+}
+
+impl fmt::Display for ForthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForthError::Vm(msg) => write!(f, "vm error: {msg}"),
+            ForthError::Interrupted => write!(f, "interrupted"),
+            ForthError::HostFnBusy(idx) => write!(f, "host function {idx} is already running"),
+            ForthError::HostPanic(msg) => write!(f, "host function panicked: {msg}"),
+            ForthError::UndefinedWord(name) => write!(f, "undefined word '{name}'"),
+            ForthError::StackUnderflow => write!(f, "stack underflow"),
+            ForthError::StackEffect { expected, actual } => {
+                write!(f, "expected {expected} results, got {actual}")
+            }
+            ForthError::InvalidCapacity(n) => write!(f, "invalid channel capacity {n}"),
+            ForthError::InvalidChannel(name) => write!(f, "'{name}' is not a channel"),
+            ForthError::NotPaused => write!(f, "no paused run"),
+            ForthError::Io(msg) => write!(f, "io error: {msg}"),
+            ForthError::SourceTooLong(len) => write!(f, "source of {len} bytes is too long"),
+            ForthError::OutOfMemory(size) => write!(f, "no free heap block of {size} bytes"),
+            ForthError::InvalidAddress(addr) => write!(f, "{addr} is not an allocated block"),
+            ForthError::DictionaryFull => write!(f, "dictionary full"),
+        }
+    }
+}
+
+impl std::error::Error for ForthError {}
+
+pub type Result<T> = std::result::Result<T, ForthError>;
@@ -1,6 +1,22 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
 
 use crate::align;
+use crate::backtrace::Frame;
+use crate::coverage::Coverage;
+use crate::debugger::Debugger;
+use crate::error::{ForthError, Result};
+use crate::heap::Heap;
+use crate::host::BoxedHostFn;
+use crate::init_dictionary::parse_number;
+use crate::input_stream::{SourceLocation, in_stream_location};
+use crate::interrupt::InterruptHandle;
+use crate::memory::HEADROOM;
+use crate::optimizer::Optimizer;
+use crate::profiler::Profiler;
+use crate::source::EVALUATE_BUFFER;
+use crate::tester::Tester;
+use crate::trace::TraceHook;
 use crate::{COMPILING, INTERPRETING, LEN_MASK, MAX_WORD_LEN, docol, mmap};
 use toyvm::VM;
 use toyvm::opcode;
@@ -9,8 +25,16 @@ pub(crate) const NEXT: u8 = 0xff;
 pub(crate) const OVER: u8 = NEXT - 1;
 pub(crate) const ROT: u8 = OVER - 1;
 pub(crate) const DIV_MOD: u8 = ROT - 1;
-
-fn forth_opcodes(vm: &mut VM, ip: &mut usize, op: u8) -> bool {
+/// ( fn_idx -- ) calls a host function registered with `ForthVM::register`.
+/// handled by `ForthVM::step`. `forth_opcodes` stops `vm.run` instead,
+/// see `ForthVM::run_word`
+pub(crate) const HOST_CALL: u8 = DIV_MOD - 1;
+/// ( a -- a+n ) n is the cell at IC
+pub(crate) const LIT_ADD: u8 = HOST_CALL - 1;
+/// ( -- x ) x is stored at the address in the cell at IC
+pub(crate) const LIT_FETCH: u8 = LIT_ADD - 1;
+
+pub(crate) fn forth_opcodes(vm: &mut VM, ip: &mut usize, op: u8) -> bool {
     match op {
         NEXT => {
             // IC points to code_ptr of the  next word to execute.
@@ -32,6 +56,16 @@ fn forth_opcodes(vm: &mut VM, ip: &mut usize, op: u8) -> bool {
             vm.push_i32(r);
             vm.push_i32(q);
         }
+        LIT_ADD => {
+            let n = read_operand(vm);
+            let a = vm.pop_i32();
+            vm.push_i32(a.wrapping_add(n));
+        }
+        LIT_FETCH => {
+            let addr = read_operand(vm);
+            let x = vm.read_i32(addr as usize);
+            vm.push_i32(x);
+        }
         OVER => {
             // ( a b -- a b a )
             let b = vm.pop_i32();
@@ -40,6 +74,12 @@ fn forth_opcodes(vm: &mut VM, ip: &mut usize, op: u8) -> bool {
             vm.push_i32(b);
             vm.push_i32(a);
         }
+        HOST_CALL => {
+            // host functions need the whole ForthVM. remember where to go on
+            // and end the run, the caller of vm.run makes the call
+            vm.write_i32(*ip as i32, mmap::HOST_RESUME);
+            *ip = mmap::HALT_CODE;
+        }
         ROT => {
             // ( a b c -- b c a )
             let c = vm.pop_i32();
@@ -54,29 +94,94 @@ fn forth_opcodes(vm: &mut VM, ip: &mut usize, op: u8) -> bool {
     true
 }
 
+/// cell at IC, moves IC past it
+fn read_operand(vm: &mut VM) -> i32 {
+    let ic = vm.read_i32(mmap::IC);
+    vm.write_i32(ic + 4, mmap::IC);
+    vm.read_i32(ic as usize)
+}
+
+/// outcome of a fuel limited run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    /// `bye` was executed
+    Done,
+    /// fuel ran out before the word finished. continue with `resume` or give up with `abort`
+    OutOfFuel,
+    /// the debugger stopped at a breakpoint, watchpoint or after a step
+    Stopped,
+}
+
 pub struct VmConfig {
+    /// initial size of the vm memory
     pub memory_size_bytes: usize,
+    /// the memory grows up to this size when the dictionary needs room.
+    /// values below `memory_size_bytes` keep the memory at its initial size
+    pub max_memory_size_bytes: usize,
     pub parameter_stack_size_cells: usize,
     pub return_stack_size_cells: usize,
     pub call_stack_size_cells: usize,
     pub locals_stack_size_cells: usize,
+    /// region after the stacks used by `allocate`
+    pub heap_size_bytes: usize,
 }
 
 pub struct ForthVM {
     pub(crate) vm: VM,
     vocabulary: HashMap<i32, String>,
+    /// ip of a run that ran out of fuel
+    pub(crate) paused_ip: Option<usize>,
+    interrupt: InterruptHandle,
+    pub(crate) host_fns: Vec<Option<BoxedHostFn>>,
+    pub(crate) user_data: Option<Box<dyn Any>>,
+    /// task control block of the interpreter, see `init_task_words`
+    pub(crate) main_task: i32,
+    /// thread of a task that was not activated or has finished, it stops the task
+    pub(crate) task_done: i32,
+    /// addresses of the channels made by `new_channel`
+    pub(crate) channels: HashSet<i32>,
+    pub(crate) trace_hook: Option<TraceHook>,
+    pub(crate) profiler: Option<Profiler>,
+    pub(crate) debugger: Debugger,
+    /// where words created by `create` were defined, by header address
+    pub(crate) locations: HashMap<i32, SourceLocation>,
+    /// set with `set_source_location`
+    pub(crate) source: SourceLocation,
+    /// free part of the region holding the sources of `evaluate`
+    pub(crate) evaluate_top: i32,
+    pub(crate) evaluate_end: i32,
+    pub(crate) last_backtrace: Vec<Frame>,
+    pub(crate) coverage: Option<Coverage>,
+    pub(crate) tester: Tester,
+    pub(crate) optimizer: Optimizer,
+    pub(crate) heap: Heap,
+    /// vm functions added by `vm_call`, needed to rebuild the vm when the memory grows
+    pub(crate) vm_fns: Vec<toyvm::VmFn>,
+    pub(crate) dict_start: i32,
+    pub(crate) memory_limit: usize,
+    /// `here` when the memory was last checked, see `check_dictionary`
+    pub(crate) checked_here: i32,
+    /// `set_here` was refused
+    pub(crate) dictionary_full: bool,
 }
 
 impl ForthVM {
     pub fn from_config(conf: VmConfig) -> Self {
-        let memory = vec![0; conf.memory_size_bytes];
+        // stacks, evaluate sources, heap and dictionary in this order,
+        // so only the dictionary has to grow
+        let pstack_top = mmap::STACKS + conf.parameter_stack_size_cells * 4;
+        let rstack_top = pstack_top + conf.return_stack_size_cells * 4;
+        let lstack_top = rstack_top + conf.locals_stack_size_cells * 4;
+        let cstack_top = lstack_top + conf.call_stack_size_cells * 4;
+        let evaluate_start = cstack_top + 4;
+        let heap_start = evaluate_start + EVALUATE_BUFFER;
+        let heap_end = align(heap_start as i32 + conf.heap_size_bytes as i32) as usize;
+        let dict_start = heap_end;
+
+        let size = conf.memory_size_bytes.max(dict_start + HEADROOM);
+        let memory = vec![0; size];
         let functions = Vec::new();
 
-        let cstack_top = memory.len() - 4;
-        let lstack_top = cstack_top - conf.call_stack_size_cells * 4;
-        let rstack_top = lstack_top - conf.locals_stack_size_cells * 4;
-        let pstack_top = rstack_top - conf.return_stack_size_cells * 4;
-
         let mut vm = VM::new(memory, functions, mmap::DSP, mmap::CTOP);
 
         vm.write_i32(pstack_top as i32, mmap::DSP);
@@ -92,20 +197,48 @@ impl ForthVM {
         // vm.write_i32(0, mmap::IC);
         // vm.write_i32(0, mmap::A0);
 
-        vm.write_i32(mmap::DICT as i32, mmap::HERE);
+        vm.write_i32(dict_start as i32, mmap::HERE);
 
         vm.write_i32(rstack_top as i32 + 4, mmap::IN_STREAM);
         vm.write_i32(lstack_top as i32, mmap::INPUT_BUFFER);
-        vm.write_i32(lstack_top as i32, mmap::INPUT_BUFFER_IDX);
+        vm.write_i32(lstack_top as i32 + 1, mmap::SOURCE_ADDR);
 
         vm.write_u8(NEXT, mmap::COLD_START);
 
+        vm.write_i32(mmap::HALT_XT as i32, mmap::HALT);
+        vm.write_i32(mmap::HALT_CODE as i32, mmap::HALT_XT);
+        vm.write_u8(opcode::END, mmap::HALT_CODE);
+
         vm.write(mmap::DOCOL, &docol());
 
         vm.add_unknown_op_handler(&forth_opcodes);
         ForthVM {
             vm,
             vocabulary: HashMap::new(),
+            paused_ip: None,
+            interrupt: InterruptHandle::default(),
+            host_fns: Vec::new(),
+            user_data: None,
+            main_task: 0,
+            task_done: 0,
+            channels: HashSet::new(),
+            trace_hook: None,
+            profiler: None,
+            debugger: Debugger::default(),
+            locations: HashMap::new(),
+            source: SourceLocation::default(),
+            evaluate_top: evaluate_start as i32,
+            evaluate_end: heap_start as i32,
+            last_backtrace: Vec::new(),
+            coverage: None,
+            tester: Tester::default(),
+            optimizer: Optimizer::default(),
+            heap: Heap::new(heap_start as i32, heap_end as i32),
+            vm_fns: Vec::new(),
+            dict_start: dict_start as i32,
+            memory_limit: conf.max_memory_size_bytes.max(size),
+            checked_here: dict_start as i32,
+            dictionary_full: false,
         }
     }
 
@@ -145,21 +278,233 @@ impl ForthVM {
         (base - top) / 4
     }
 
+    /// parameter stack, bottom first
+    pub fn pstack(&self) -> Vec<i32> {
+        let base = self.read_i32(mmap::S0 as i32);
+        (0..self.pstack_depth())
+            .map(|i| self.read_i32(base - i * 4))
+            .collect()
+    }
+
+    /// return stack, bottom first
+    pub fn rstack(&self) -> Vec<i32> {
+        let base = self.read_i32(mmap::R0 as i32);
+        (0..self.rstack_depth())
+            .map(|i| self.read_i32(base - i * 4))
+            .collect()
+    }
+
+    /// runs the word directly on toyvm. interrupts and memory growth are only
+    /// handled by `try_run_word`, which is also used while tracing, profiling,
+    /// measuring coverage or while an `InterruptHandle` is held.
+    /// an interrupted run returns after the abort
     pub fn run_word(&mut self, word_idx: usize) {
+        if self.is_instrumented() || self.interrupt.is_watched() {
+            let result = self.try_run_word(word_idx);
+            if result != Err(ForthError::Interrupted) {
+                result.unwrap();
+            }
+            return;
+        }
+
+        let mut ip = self.cold_start(word_idx);
+        loop {
+            self.vm.run(&mut ip).unwrap();
+            let resume = self.read_i32(mmap::HOST_RESUME as i32);
+            if resume == 0 {
+                return;
+            }
+            self.write_i32(0, mmap::HOST_RESUME as i32);
+            self.call_host_fn().unwrap();
+            ip = resume as usize;
+        }
+    }
+
+    /// a hook wants to see every NEXT
+    fn is_instrumented(&self) -> bool {
+        self.trace_hook.is_some() || self.profiler.is_some() || self.coverage.is_some()
+    }
+
+    /// like `run_word`, but returns errors and can be stopped with an `InterruptHandle`
+    pub fn try_run_word(&mut self, word_idx: usize) -> Result<()> {
+        let ip = self.cold_start(word_idx);
+        self.run_checked(ip, None).map(|_| ())
+    }
+
+    /// runs word but stops after `fuel` NEXT dispatches.
+    /// an unfinished run can be continued with `resume` or dropped with `abort`
+    pub fn run_word_with_fuel(&mut self, word_idx: usize, fuel: u64) -> Result<RunStatus> {
+        let ip = self.cold_start(word_idx);
+        self.run_checked(ip, Some(fuel))
+    }
+
+    /// continues a run that ran out of fuel
+    pub fn resume(&mut self, fuel: u64) -> Result<RunStatus> {
+        match self.paused_ip.take() {
+            Some(ip) => self.run_checked(ip, Some(fuel)),
+            None => Ok(RunStatus::Done),
+        }
+    }
+
+    /// calls word `name` with `args` (last one on top of the stack) and returns
+    /// the values it leaves on the parameter stack.
+    /// can be used from inside a host function
+    pub fn call(&mut self, name: &str, args: &[i32]) -> Result<Vec<i32>> {
+        let word = self
+            .find(name)
+            .ok_or_else(|| ForthError::UndefinedWord(name.to_string()))?;
+        let xt = self.cfa(word);
+        let base = self.pstack_depth();
+
+        for arg in args {
+            self.push_i32(*arg);
+        }
+        self.execute_xt(xt)?;
+
+        let depth = self.pstack_depth();
+        if depth < base {
+            let s0 = self.read_i32(mmap::S0 as i32);
+            self.write_i32(s0 - base * 4, mmap::DSP as i32);
+            return Err(ForthError::StackUnderflow);
+        }
+
+        let mut results = vec![0; (depth - base) as usize];
+        for value in results.iter_mut().rev() {
+            *value = self.pop_i32();
+        }
+        Ok(results)
+    }
+
+    /// runs `xt` to its end without touching the parameter stack.
+    /// can be used from inside a host function
+    pub(crate) fn execute_xt(&mut self, xt: i32) -> Result<()> {
+        // we might be called from a host function in the middle of a run
+        let ic = self.read_i32(mmap::IC as i32);
+        let a0 = self.read_i32(mmap::A0 as i32);
+
+        // like execute, but the word returns to HALT instead of the next cell
+        self.write_i32(mmap::HALT as i32, mmap::IC as i32);
+        self.write_i32(xt, mmap::A0 as i32);
+        let ip = self.read_i32(xt) as usize;
+        let attached = self.detach_debugger();
+        let result = self.run_checked(ip, None);
+        self.attach_debugger(attached);
+
+        self.write_i32(ic, mmap::IC as i32);
+        self.write_i32(a0, mmap::A0 as i32);
+
+        if let Err(err) = result {
+            if err == ForthError::Interrupted {
+                // make an outer run stop as well
+                self.interrupt.interrupt();
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// like `call`, but fails unless the word leaves exactly `n_results` values
+    pub fn call_checked(&mut self, name: &str, args: &[i32], n_results: usize) -> Result<Vec<i32>> {
+        let results = self.call(name, args)?;
+        if results.len() != n_results {
+            return Err(ForthError::StackEffect {
+                expected: n_results,
+                actual: results.len(),
+            });
+        }
+        Ok(results)
+    }
+
+    /// handle to stop a running word from another thread
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_ip.is_some()
+    }
+
+    /// drops a paused run and resets parameter and return stacks.
+    /// a definition that was still being compiled is removed from the dictionary
+    pub fn abort(&mut self) {
+        self.save_backtrace();
+        self.paused_ip = None;
+        self.stop_current_task();
+        let s0 = self.vm.read_i32(mmap::S0);
+        let r0 = self.vm.read_i32(mmap::R0);
+        self.vm.write_i32(s0, mmap::DSP);
+        self.vm.write_i32(r0, mmap::RSP);
+
+        if self.is_compiling() {
+            let unfinished = self.latest();
+            self.set_latest(self.read_i32(unfinished));
+            self.rewind_here(unfinished);
+            self.set_state(INTERPRETING);
+        }
+    }
+
+    pub(crate) fn cold_start(&mut self, word_idx: usize) -> usize {
+        self.paused_ip = None;
         let _cfa = self.cfa(word_idx as i32);
         self.vm.write_i32(_cfa, mmap::START_ADR);
         self.vm.write_i32(mmap::START_ADR as i32, mmap::IC);
-        let mut ip = mmap::COLD_START;
+        mmap::COLD_START
+    }
 
-        self.vm.run(&mut ip).unwrap();
+    pub(crate) fn run_checked(
+        &mut self,
+        mut ip: usize,
+        mut fuel: Option<u64>,
+    ) -> Result<RunStatus> {
+        let profile_depth = self.profile_depth();
+        loop {
+            if self.vm.read_u8(ip) == NEXT {
+                self.check_dictionary()?;
+                if self.interrupt.take() {
+                    self.abort();
+                    return Err(ForthError::Interrupted);
+                }
+                if self.debug_check() {
+                    self.paused_ip = Some(ip);
+                    return Ok(RunStatus::Stopped);
+                }
+                if let Some(fuel) = fuel.as_mut() {
+                    if *fuel == 0 {
+                        self.paused_ip = Some(ip);
+                        return Ok(RunStatus::OutOfFuel);
+                    }
+                    *fuel -= 1;
+                }
+                self.trace_next();
+                self.profile_next();
+                self.coverage_next();
+            }
+
+            if !self.step(&mut ip)? {
+                self.profile_unwind(profile_depth);
+                return Ok(RunStatus::Done);
+            }
+            self.profile_step();
+        }
+    }
+
+    /// executes one opcode. returns false after `bye`
+    fn step(&mut self, ip: &mut usize) -> Result<bool> {
+        if self.vm.read_u8(*ip) == HOST_CALL {
+            self.call_host_fn()?;
+            *ip += 1;
+            return Ok(true);
+        }
+
+        self.vm.step(ip).map_err(|err| {
+            self.save_backtrace();
+            ForthError::Vm(format!("{err:?}"))
+        })
     }
 
     pub fn run_word_debug(&mut self, word_idx: usize) {
         self.print_dictionary();
-        let _cfa = self.cfa(word_idx as i32);
-        self.vm.write_i32(_cfa, mmap::START_ADR);
-        self.vm.write_i32(mmap::START_ADR as i32, mmap::IC);
-        let mut ip = mmap::COLD_START;
+        let mut ip = self.cold_start(word_idx);
 
         let stdin = std::io::stdin();
         let mut buf = String::new();
@@ -167,7 +512,7 @@ impl ForthVM {
         let op = self.read_u8(ip as i32);
         println!("{} {}", ip, toyvm::opcode::opcode(op));
 
-        while self.vm.step(&mut ip).unwrap() {
+        while self.step(&mut ip).unwrap() {
             self.print_4th_vars();
             self.print_pstack();
             self.print_rstack();
@@ -187,6 +532,26 @@ impl ForthVM {
         read_next_char(&mut self.vm)
     }
 
+    /// true if the current source holds more than white space and `\` comments
+    pub fn input_pending(&self) -> bool {
+        let source = self.read_i32(mmap::SOURCE_ADDR as i32);
+        let len = self.read_i32(mmap::SOURCE_LEN as i32);
+        let to_in = self.read_i32(mmap::TO_IN as i32);
+
+        let mut in_comment = false;
+        for i in source + to_in..source + len {
+            let c = self.read_u8(i) as char;
+            if in_comment {
+                in_comment = c != '\n';
+            } else if c == '\\' {
+                in_comment = true;
+            } else if !c.is_ascii_whitespace() {
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn write_str(&mut self, idx: usize, s: &str) {
         write_str(&mut self.vm, idx, s);
     }
@@ -203,8 +568,31 @@ impl ForthVM {
         self.vm.read_i32(mmap::HERE)
     }
 
-    pub fn set_here(&mut self, value: i32) {
+    /// fails with `ForthError::DictionaryFull` when the memory limit is reached,
+    /// `here` is left unchanged then
+    pub fn set_here(&mut self, value: i32) -> Result<()> {
+        if !self.reserve_dictionary(value) {
+            return Err(ForthError::DictionaryFull);
+        }
+        self.vm.write_i32(value, mmap::HERE);
+        self.checked_here = value;
+        Ok(())
+    }
+
+    /// `set_here` for code compiled by a running word. when the dictionary is
+    /// full the run is aborted at its next NEXT, see `check_dictionary`
+    fn advance_here(&mut self, value: i32) -> bool {
+        if self.set_here(value).is_err() {
+            self.dictionary_full = true;
+            return false;
+        }
+        true
+    }
+
+    /// moves `here` back to `value`, e.g. to drop an unfinished definition
+    pub(crate) fn rewind_here(&mut self, value: i32) {
         self.vm.write_i32(value, mmap::HERE);
+        self.checked_here = value;
     }
 
     pub fn base(&self) -> i32 {
@@ -231,6 +619,62 @@ impl ForthVM {
         self.state() == INTERPRETING
     }
 
+    /// reserves `n` bytes in the dictionary. returns start address
+    pub fn allot(&mut self, n: i32) -> Result<i32> {
+        let here = self.here();
+        self.set_here(align(here + n))?;
+        Ok(here)
+    }
+
+    pub fn read_string(&self, addr: i32, len: i32) -> String {
+        let mut s = String::new();
+
+        for i in 0..len {
+            let c = self.read_u8(addr + i) as char;
+            s.push(c);
+        }
+
+        s
+    }
+
+    /// name of the word with this code field address
+    pub fn word_name(&self, cfa: i32) -> Option<&str> {
+        self.vocabulary.get(&cfa).map(String::as_str)
+    }
+
+    /// where the word was defined. None for words defined from rust
+    pub fn locate(&self, name: &str) -> Option<&SourceLocation> {
+        self.locations.get(&self.find(name)?)
+    }
+
+    /// for hosts that fill the input buffer themselves
+    pub fn set_source_location(&mut self, file: &str, line: usize) {
+        self.source = SourceLocation {
+            file: file.to_string(),
+            line,
+            column: 0,
+        };
+        self.write_i32(0, mmap::SOURCE_STREAM as i32);
+        self.write_i32(0, mmap::WORD_COLUMN as i32);
+    }
+
+    /// where the current source came from. column is the start
+    /// of the word read last by `word`
+    pub fn source_location(&self) -> SourceLocation {
+        let mut location = if self.read_i32(mmap::SOURCE_STREAM as i32) != 0 {
+            in_stream_location()
+        } else {
+            self.source.clone()
+        };
+        location.column = self.read_i32(mmap::WORD_COLUMN as i32) as usize;
+        location
+    }
+
+    /// code field address of `name`, 0 if it is not defined
+    pub(crate) fn xt_of(&self, name: &str) -> i32 {
+        self.find(name).map(|w| self.cfa(w)).unwrap_or(0)
+    }
+
     pub fn cfa(&self, idx: i32) -> i32 {
         let len = self.read_u8(idx + 4) & LEN_MASK;
         let n = (len as usize).min(MAX_WORD_LEN) as i32;
@@ -239,6 +683,10 @@ impl ForthVM {
     }
 
     pub fn find(&self, name: &str) -> Option<i32> {
+        // headers of :noname words have no name
+        if name.is_empty() {
+            return None;
+        }
         let mut current_word_idx = self.latest();
         loop {
             let len = (self.vm.read_u8(current_word_idx as usize + 4) & LEN_MASK) as usize;
@@ -262,6 +710,7 @@ impl ForthVM {
 
     pub fn vm_call(&mut self, name: &str, f: toyvm::VmFn) -> (i32, i32) {
         let fn_idx = self.vm.add_function(f);
+        self.vm_fns.push(f);
         let bytes = fn_idx.to_ne_bytes();
         let word_adr = self.builtin(
             name,
@@ -283,6 +732,7 @@ impl ForthVM {
     }
 
     pub fn builtin_ex(&mut self, name: &str, flags: u8, code: &[u8]) -> i32 {
+        self.reserve_definition(name, 4 + code.len());
         let new_last_word_idx = self.here();
         self.write_previous_idx();
         self.write_name(name, flags);
@@ -296,17 +746,20 @@ impl ForthVM {
         new_last_word_idx
     }
 
+    /// numbers in `calls` are decimal unless they have a prefix like `$ff`
     pub fn colon_def(&mut self, name: &str, calls: &[&str]) -> i32 {
         self.colon_def_ex(name, 0, calls)
     }
 
     pub fn colon_def_ex(&mut self, name: &str, flags: u8, calls: &[&str]) -> i32 {
+        self.reserve_definition(name, 4 + calls.len() * 4);
         let new_last_word_idx = self.here();
         self.write_previous_idx();
         self.write_name(name, flags);
         self.write_docol_addr();
         self.write_colon_def(calls);
         self.set_latest(new_last_word_idx);
+        self.finish_colon_def(new_last_word_idx);
 
         self.vocabulary
             .insert(self.cfa(new_last_word_idx), name.to_string());
@@ -314,10 +767,23 @@ impl ForthVM {
         new_last_word_idx
     }
 
-    fn compile_cell(&mut self, value: i32) {
+    /// makes room for the header of `name` followed by `size` bytes.
+    /// panics when the memory limit is reached, like for an undefined word in `colon_def`
+    fn reserve_definition(&mut self, name: &str, size: usize) {
+        let header = 4 + align(1 + name.len().min(MAX_WORD_LEN) as i32);
+        let end = self.here() + header + size as i32;
+        assert!(
+            self.reserve_dictionary(end),
+            "dictionary full, no room for '{name}'"
+        );
+    }
+
+    /// nothing is written when the dictionary is full, see `advance_here`
+    pub(crate) fn compile_cell(&mut self, value: i32) {
         let next_empty_space = self.here();
-        self.write_i32(value, next_empty_space);
-        self.set_here(next_empty_space + 4);
+        if self.advance_here(next_empty_space + 4) {
+            self.write_i32(value, next_empty_space);
+        }
     }
 
     pub(crate) fn write_previous_idx(&mut self) {
@@ -328,12 +794,13 @@ impl ForthVM {
         let next_empty_space = self.here();
 
         let len = name.len();
-        self.write_u8(len as u8 | flags, next_empty_space);
         let n = len.min(MAX_WORD_LEN);
+        if !self.advance_here(align(next_empty_space + n as i32 + 1)) {
+            return;
+        }
+        self.write_u8(len as u8 | flags, next_empty_space);
         self.vm
             .write(next_empty_space as usize + 1, &name.as_bytes()[..n]);
-
-        self.set_here(align(next_empty_space + n as i32 + 1));
     }
 
     fn write_codeword_builtin(&mut self) {
@@ -343,12 +810,12 @@ impl ForthVM {
     fn write_code(&mut self, code: &[u8]) {
         let next_empty_space = self.here();
         let n = code.len() as i32;
-
-        self.vm.write(next_empty_space as usize, code);
-        self.set_here(align(next_empty_space + n));
+        if self.advance_here(align(next_empty_space + n)) {
+            self.vm.write(next_empty_space as usize, code);
+        }
     }
 
-    fn write_docol_addr(&mut self) {
+    pub(crate) fn write_docol_addr(&mut self) {
         self.compile_cell(mmap::DOCOL as i32);
     }
 
@@ -356,7 +823,7 @@ impl ForthVM {
         for call in calls {
             let c = if let Some(w) = self.find(call) {
                 self.cfa(w)
-            } else if let Ok(n) = call.parse() {
+            } else if let Ok(n) = parse_number(call.as_bytes(), 10) {
                 n
             } else {
                 panic!("{call}?")
@@ -521,36 +988,34 @@ fn write_str(vm: &mut VM, idx: usize, s: &str) {
 }
 
 fn print_input_buffer(vm: &VM) {
-    let input_buffer = vm.read_i32(mmap::INPUT_BUFFER);
-    let input_buffer_idx = vm.read_i32(mmap::INPUT_BUFFER_IDX);
+    let source = vm.read_i32(mmap::SOURCE_ADDR);
+    let len = vm.read_i32(mmap::SOURCE_LEN);
+    let to_in = vm.read_i32(mmap::TO_IN);
 
-    let len = vm.read_u8(input_buffer as usize);
     let mut s = String::new();
-
     for i in 0..len {
-        let c = vm.read_u8(input_buffer as usize + 1 + i as usize);
+        let c = vm.read_u8((source + i) as usize);
         s.push(c as char);
     }
 
-    println!(
-        "len: {len} input_buffer: {input_buffer} input_buffer_idx: {input_buffer_idx} s: \'{s}\'"
-    );
+    println!("len: {len} source: {source} >in: {to_in} s: \'{s}\'");
 }
 
 pub(crate) fn read_next_char(vm: &mut VM) -> Option<u8> {
-    let input_buffer = vm.read_i32(mmap::INPUT_BUFFER);
-    let input_buffer_idx = vm.read_i32(mmap::INPUT_BUFFER_IDX);
-    let len = vm.read_u8(input_buffer as usize);
+    let source = vm.read_i32(mmap::SOURCE_ADDR);
+    let len = vm.read_i32(mmap::SOURCE_LEN);
+    let to_in = vm.read_i32(mmap::TO_IN);
 
-    if len == 0 || input_buffer_idx - input_buffer > len as i32 {
+    if to_in >= len {
         return None;
     }
 
-    let c = vm.read_u8(input_buffer_idx as usize);
-    vm.write_i32(input_buffer_idx + 1, mmap::INPUT_BUFFER_IDX);
+    let c = vm.read_u8((source + to_in) as usize);
+    vm.write_i32(to_in + 1, mmap::TO_IN);
     Some(c)
 }
 
+/// makes `s` the current source, read from the terminal input buffer
 pub(crate) fn fill_input_buffer(vm: &mut VM, s: &str) {
     let input_buf = vm.read_i32(mmap::INPUT_BUFFER);
 
@@ -558,5 +1023,8 @@ pub(crate) fn fill_input_buffer(vm: &mut VM, s: &str) {
     vm.write_u8(len as u8, input_buf as usize);
     vm.write(input_buf as usize + 1, s.as_bytes());
 
-    vm.write_i32(input_buf + 1, mmap::INPUT_BUFFER_IDX);
+    vm.write_i32(input_buf + 1, mmap::SOURCE_ADDR);
+    vm.write_i32(len as i32, mmap::SOURCE_LEN);
+    vm.write_i32(0, mmap::TO_IN);
+    vm.write_i32(0, mmap::SOURCE_ID);
 }
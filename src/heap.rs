@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    ForthVM, align,
+    error::{ForthError, Result},
+};
+
+/// ior of `allocate`, `free` and `resize`, the ANS throw codes
+const ALLOCATE_ERROR: i32 = -59;
+const FREE_ERROR: i32 = -60;
+const RESIZE_ERROR: i32 = -61;
+
+/// first fit allocator over `start..end` of the vm memory
+#[derive(Default)]
+pub(crate) struct Heap {
+    pub(crate) start: i32,
+    pub(crate) end: i32,
+    /// allocated blocks, address to size in bytes
+    blocks: BTreeMap<i32, i32>,
+}
+
+impl Heap {
+    pub(crate) fn new(start: i32, end: i32) -> Self {
+        Heap {
+            start,
+            end,
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    fn allocate(&mut self, size: i32) -> Option<i32> {
+        let mut gap = self.start;
+        for (&addr, &len) in &self.blocks {
+            if addr - gap >= size {
+                break;
+            }
+            gap = addr + len;
+        }
+        if self.end - gap < size {
+            return None;
+        }
+        self.blocks.insert(gap, size);
+        Some(gap)
+    }
+
+    /// grows or shrinks the block at `addr` without moving it
+    fn resize_in_place(&mut self, addr: i32, size: i32) -> bool {
+        let limit = self
+            .blocks
+            .range(addr + 1..)
+            .next()
+            .map_or(self.end, |(&next, _)| next);
+        if addr + size > limit {
+            return false;
+        }
+        self.blocks.insert(addr, size);
+        true
+    }
+}
+
+impl ForthVM {
+    pub(crate) fn init_heap_words(&mut self) {
+        // ( u -- a-addr ior )
+        self.register_native("allocate", |forth| {
+            let size = forth.pop_i32();
+            let (addr, ior) = match forth.allocate(size as u32 as usize) {
+                Ok(addr) => (addr, 0),
+                Err(_) => (0, ALLOCATE_ERROR),
+            };
+            forth.push_i32(addr);
+            forth.push_i32(ior);
+        });
+        // ( a-addr -- ior )
+        self.register_native("free", |forth| {
+            let addr = forth.pop_i32();
+            let ior = if forth.free(addr).is_ok() {
+                0
+            } else {
+                FREE_ERROR
+            };
+            forth.push_i32(ior);
+        });
+        // ( a-addr1 u -- a-addr2 ior ) a-addr1 stays valid on failure
+        self.register_native("resize", |forth| {
+            let size = forth.pop_i32();
+            let addr = forth.pop_i32();
+            let (addr, ior) = match forth.resize(addr, size as u32 as usize) {
+                Ok(addr) => (addr, 0),
+                Err(_) => (addr, RESIZE_ERROR),
+            };
+            forth.push_i32(addr);
+            forth.push_i32(ior);
+        });
+    }
+
+    /// reserves `size` bytes of the heap configured with `VmConfig::heap_size_bytes`.
+    /// the block is cell aligned, its contents are undefined
+    pub fn allocate(&mut self, size: usize) -> Result<i32> {
+        let len = self.block_len(size)?;
+        self.heap.allocate(len).ok_or(ForthError::OutOfMemory(size))
+    }
+
+    /// allocates a block holding a copy of `data`, e.g. to hand a buffer to forth code
+    pub fn allocate_bytes(&mut self, data: &[u8]) -> Result<i32> {
+        let addr = self.allocate(data.len())?;
+        self.vm.write(addr as usize, data);
+        Ok(addr)
+    }
+
+    /// returns a block from `allocate` to the heap
+    pub fn free(&mut self, addr: i32) -> Result<()> {
+        self.heap
+            .blocks
+            .remove(&addr)
+            .map(|_| ())
+            .ok_or(ForthError::InvalidAddress(addr))
+    }
+
+    /// changes the size of a block from `allocate`, moving it if needed.
+    /// on error the block is unchanged
+    pub fn resize(&mut self, addr: i32, size: usize) -> Result<i32> {
+        let Some(&old) = self.heap.blocks.get(&addr) else {
+            return Err(ForthError::InvalidAddress(addr));
+        };
+        let len = self.block_len(size)?;
+        if self.heap.resize_in_place(addr, len) {
+            return Ok(addr);
+        }
+
+        let new = self.allocate(size)?;
+        let data: Vec<u8> = (0..old).map(|i| self.read_u8(addr + i)).collect();
+        self.vm.write(new as usize, &data);
+        self.heap.blocks.remove(&addr);
+        Ok(new)
+    }
+
+    /// aligned size of a block of `size` bytes, sizes larger than the heap fail
+    /// before they can overflow
+    fn block_len(&self, size: usize) -> Result<i32> {
+        if size > (self.heap.end - self.heap.start) as usize {
+            return Err(ForthError::OutOfMemory(size));
+        }
+        Ok(align(size.max(1) as i32))
+    }
+
+    /// bytes of the heap not allocated
+    pub fn heap_free(&self) -> usize {
+        let used: i32 = self.heap.blocks.values().sum();
+        (self.heap.end - self.heap.start - used) as usize
+    }
+}
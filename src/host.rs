@@ -0,0 +1,236 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+use toyvm::{VM, opcode};
+
+use crate::{
+    FALSE, ForthVM, TRUE,
+    error::{ForthError, Result},
+    forthvm::{HOST_CALL, NEXT},
+};
+
+/// value taken from the parameter stack as host function argument
+pub trait FromStack: Sized {
+    fn from_stack(vm: &mut VM) -> Self;
+}
+
+/// value pushed onto the parameter stack as host function result
+pub trait ToStack {
+    fn to_stack(self, vm: &mut VM);
+}
+
+impl FromStack for i32 {
+    fn from_stack(vm: &mut VM) -> Self {
+        vm.pop_i32()
+    }
+}
+
+impl FromStack for bool {
+    fn from_stack(vm: &mut VM) -> Self {
+        vm.pop_i32() != FALSE
+    }
+}
+
+/// ( c-addr len )
+impl FromStack for String {
+    fn from_stack(vm: &mut VM) -> Self {
+        let len = vm.pop_i32();
+        let ptr = vm.pop_i32();
+        let mut s = String::new();
+
+        for i in 0..len {
+            let c = vm.read_u8((ptr + i) as usize) as char;
+            s.push(c);
+        }
+
+        s
+    }
+}
+
+impl ToStack for () {
+    fn to_stack(self, _vm: &mut VM) {}
+}
+
+impl ToStack for i32 {
+    fn to_stack(self, vm: &mut VM) {
+        vm.push_i32(self);
+    }
+}
+
+impl ToStack for bool {
+    fn to_stack(self, vm: &mut VM) {
+        vm.push_i32(if self { TRUE } else { FALSE });
+    }
+}
+
+/// values are pushed in order, last one ends up on top
+impl ToStack for Vec<i32> {
+    fn to_stack(self, vm: &mut VM) {
+        for value in self {
+            vm.push_i32(value);
+        }
+    }
+}
+
+impl<A: ToStack, B: ToStack> ToStack for (A, B) {
+    fn to_stack(self, vm: &mut VM) {
+        self.0.to_stack(vm);
+        self.1.to_stack(vm);
+    }
+}
+
+impl<A: ToStack, B: ToStack, C: ToStack> ToStack for (A, B, C) {
+    fn to_stack(self, vm: &mut VM) {
+        self.0.to_stack(vm);
+        self.1.to_stack(vm);
+        self.2.to_stack(vm);
+    }
+}
+
+/// rust closure callable from forth.
+/// arguments are popped from the parameter stack (last argument is top of stack),
+/// the result is pushed back
+pub trait HostFn<Args>: 'static {
+    fn call(&mut self, vm: &mut VM);
+}
+
+impl<F, R> HostFn<()> for F
+where
+    F: FnMut() -> R + 'static,
+    R: ToStack,
+{
+    fn call(&mut self, vm: &mut VM) {
+        self().to_stack(vm);
+    }
+}
+
+impl<F, A, R> HostFn<(A,)> for F
+where
+    F: FnMut(A) -> R + 'static,
+    A: FromStack,
+    R: ToStack,
+{
+    fn call(&mut self, vm: &mut VM) {
+        let a = A::from_stack(vm);
+        self(a).to_stack(vm);
+    }
+}
+
+impl<F, A, B, R> HostFn<(A, B)> for F
+where
+    F: FnMut(A, B) -> R + 'static,
+    A: FromStack,
+    B: FromStack,
+    R: ToStack,
+{
+    fn call(&mut self, vm: &mut VM) {
+        let b = B::from_stack(vm);
+        let a = A::from_stack(vm);
+        self(a, b).to_stack(vm);
+    }
+}
+
+impl<F, A, B, C, R> HostFn<(A, B, C)> for F
+where
+    F: FnMut(A, B, C) -> R + 'static,
+    A: FromStack,
+    B: FromStack,
+    C: FromStack,
+    R: ToStack,
+{
+    fn call(&mut self, vm: &mut VM) {
+        let c = C::from_stack(vm);
+        let b = B::from_stack(vm);
+        let a = A::from_stack(vm);
+        self(a, b, c).to_stack(vm);
+    }
+}
+
+impl<F, A, B, C, D, R> HostFn<(A, B, C, D)> for F
+where
+    F: FnMut(A, B, C, D) -> R + 'static,
+    A: FromStack,
+    B: FromStack,
+    C: FromStack,
+    D: FromStack,
+    R: ToStack,
+{
+    fn call(&mut self, vm: &mut VM) {
+        let d = D::from_stack(vm);
+        let c = C::from_stack(vm);
+        let b = B::from_stack(vm);
+        let a = A::from_stack(vm);
+        self(a, b, c, d).to_stack(vm);
+    }
+}
+
+pub(crate) type BoxedHostFn = Box<dyn FnMut(&mut ForthVM)>;
+
+impl ForthVM {
+    /// adds a word that calls a rust closure.
+    /// `vm.register("sensor@", |ch: i32| ch * 2)`
+    /// returns (host function index, word address)
+    pub fn register<Args: 'static, F: HostFn<Args>>(&mut self, name: &str, mut f: F) -> (i32, i32) {
+        self.register_native(name, move |forth: &mut ForthVM| f.call(&mut forth.vm))
+    }
+
+    /// adds a word that calls a rust closure with full access to the ForthVM.
+    /// the closure can use the parameter stack, compile words, look up names
+    /// and reach host data set with `set_user_data`
+    pub fn register_native<F>(&mut self, name: &str, f: F) -> (i32, i32)
+    where
+        F: FnMut(&mut ForthVM) + 'static,
+    {
+        let fn_idx = self.host_fns.len() as i32;
+        self.host_fns.push(Some(Box::new(f)));
+
+        let bytes = fn_idx.to_ne_bytes();
+        let word_adr = self.builtin(
+            name,
+            &[
+                opcode::I32_CONST,
+                bytes[0],
+                bytes[1],
+                bytes[2],
+                bytes[3],
+                HOST_CALL,
+                NEXT,
+            ],
+        );
+        (fn_idx, word_adr)
+    }
+
+    /// ( fn_idx -- ) runs host function registered with `register`
+    pub(crate) fn call_host_fn(&mut self) -> Result<()> {
+        let fn_idx = self.vm.pop_i32() as usize;
+        // taken out while running, so the function can borrow the whole vm
+        let mut f = self.host_fns[fn_idx]
+            .take()
+            .ok_or(ForthError::HostFnBusy(fn_idx))?;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(self)));
+        self.host_fns[fn_idx] = Some(f);
+
+        result.map_err(|payload| {
+            self.save_backtrace();
+            let msg = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            ForthError::HostPanic(msg)
+        })
+    }
+
+    /// stores host data for native words. replaces previous data
+    pub fn set_user_data<T: Any>(&mut self, data: T) {
+        self.user_data = Some(Box::new(data));
+    }
+
+    pub fn user_data<T: Any>(&self) -> Option<&T> {
+        self.user_data.as_ref()?.downcast_ref()
+    }
+
+    pub fn user_data_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.user_data.as_mut()?.downcast_mut()
+    }
+}
@@ -1,10 +1,12 @@
 use toyvm::{VM, opcode};
 
 use crate::{
-    ForthVM, HIDDEN, IMMEDIATE, LEN_MASK, MAX_WORD_LEN, align,
+    COMPILE_ONLY, FALSE, ForthVM, HIDDEN, IMMEDIATE, LEN_MASK, MAX_WORD_LEN, TRUE, align,
     forthvm::{DIV_MOD, NEXT, OVER, ROT, fill_input_buffer, read_next_char},
     input_stream::{in_stream_from_stdin, in_stream_is_terminal, in_stream_read_line},
     mmap,
+    output_stream::out_stream_write,
+    source::EVALUATE_SOURCE,
 };
 
 impl ForthVM {
@@ -504,36 +506,6 @@ impl ForthVM {
             ],
         );
 
-        // TODO: right now same as "lit". redefine in terms of word find >cfa
-        self.builtin_ex(
-            "'",
-            0,
-            &[
-                opcode::I32_CONST,
-                ic[0],
-                ic[1],
-                ic[2],
-                ic[3],
-                opcode::I32_LOAD,
-                opcode::DUP,
-                opcode::I32_LOAD,
-                opcode::SWAP,
-                opcode::I32_CONST,
-                4,
-                0,
-                0,
-                0,
-                opcode::ADD,
-                opcode::I32_CONST,
-                ic[0],
-                ic[1],
-                ic[2],
-                ic[3],
-                opcode::I32_STORE,
-                NEXT,
-            ],
-        );
-
         // (idx + 3) & !3
         let mwl = (MAX_WORD_LEN as i32).to_ne_bytes();
         self.builtin(
@@ -646,8 +618,13 @@ impl ForthVM {
         self.vm_call("find", &find);
 
         self.vm_call("number", &number);
+        self.vm_call(">number", &to_number);
         self.vm_call(",", &comma);
-        self.vm_call("create", &create);
+        self.register_native("create", |forth| {
+            let header = forth.vm.read_i32(mmap::HERE);
+            create(&mut forth.vm);
+            forth.locations.insert(header, forth.source_location());
+        });
         self.vm_call("char", &read_char);
 
         self.colon_def(">dfa", &[">cfa", "4+", "exit"]);
@@ -660,79 +637,128 @@ impl ForthVM {
             ],
         );
 
+        self.init_optimizer_words();
         self.colon_def_ex(
             ";",
             IMMEDIATE,
-            &["lit", "exit", ",", "latest", "@", "hidden", "[", "exit"],
+            &[
+                "lit",
+                "exit",
+                ",",
+                "latest",
+                "@",
+                "hidden",
+                "(optimize)",
+                "[",
+                "exit",
+            ],
         );
 
+        // reports the word with its source location. interpret used to print
+        // just "?" here
+        self.register_native("(undefined)", |forth| {
+            let ptr = forth.vm.read_i32(mmap::IN_STREAM);
+            let name = forth.read_string(ptr, forth.read_i32(mmap::WORD_LEN as i32));
+            forth.report_error(&format!("undefined word '{name}'"));
+        });
+        // ( w -- w flag ) false when interpreting a compile-only word
+        self.register_native("(compile-only?)", |forth| {
+            let word = forth.pop_i32();
+            let compile_only = forth.read_u8(word + 4) & COMPILE_ONLY != 0;
+            let ok = !compile_only || forth.is_compiling();
+            if !ok {
+                let name = forth.xt_name(forth.cfa(word));
+                forth.report_error(&format!("'{name}' is compile-only"));
+            }
+            forth.push_i32(word);
+            forth.push_i32(if ok { TRUE } else { FALSE });
+        });
+        self.register_native("locate", |forth| {
+            let (len, ptr) = _word(&mut forth.vm);
+            let name = forth.read_string(ptr, len);
+            let msg = match forth.find(&name) {
+                None => format!("undefined word '{name}'"),
+                Some(header) => match forth.locations.get(&header) {
+                    Some(location) => format!("{name} is defined at {location}"),
+                    None => format!("{name} has no source location"),
+                },
+            };
+            out_stream_write(&format!("\n{msg}\n"));
+        });
+
         self.colon_def(
             "interpret",
             &[
-                "word",    // 0
-                "2dup",    // 1
-                "lit",     // 2
-                "0",       // 3
-                ">r",      // 4
-                "find",    // 5
-                "dup",     // 6
-                "0branch", // 7
-                "56",      // 8 "$NOT_IN_DICT" (22 - 8 * 4 => 56 )
-                "swap",    // 9
-                "drop",    // 10
-                "swap",    // 11
-                "drop",    // 12
-                "dup",     // 13
-                "4+",      // 14
-                "c@",      // 15
-                "F_IMMED", // 16
-                "and",     // 17
-                ">r",      // 18
-                ">cfa",    // 19
-                "branch",  // 20
-                "36",      // 21 "$IS_EXECUTING?" (30 - 21) 9 * 4 => 36
+                "word",            // 0
+                "2dup",            // 1
+                "lit",             // 2
+                "0",               // 3
+                ">r",              // 4
+                "find",            // 5
+                "dup",             // 6
+                "0branch",         // 7
+                "68",              // 8 "$NOT_IN_DICT" (25 - 8) 17 * 4 => 68
+                "swap",            // 9
+                "drop",            // 10
+                "swap",            // 11
+                "drop",            // 12
+                "(compile-only?)", // 13
+                "0branch",         // 14
+                "180",             // 15 "$COMPILE_ONLY_ERROR" (60 - 15) 45 * 4 => 180
+                "dup",             // 16
+                "4+",              // 17
+                "c@",              // 18
+                "F_IMMED",         // 19
+                "and",             // 20
+                ">r",              // 21
+                ">cfa",            // 22
+                "branch",          // 23
+                "36",              // 24 "$IS_EXECUTING?" (33 - 24) 9 * 4 => 36
                 // $NOT_IN_DICT:
-                "r>",      // 22
-                "1+",      // 23
-                ">r",      // 24
-                ">r",      // 25
-                "number",  // 26
-                "0=",      // 27
-                "0branch", // 28
-                "92",      // 29 "$PARSE_ERROR" (52 - 29) 23 * 4 => 92
+                "r>",      // 25
+                "1+",      // 26
+                ">r",      // 27
+                ">r",      // 28
+                "number",  // 29
+                "0=",      // 30
+                "0branch", // 31
+                "92",      // 32 "$PARSE_ERROR" (55 - 32) 23 * 4 => 92
                 // $IS_EXECUTING?:
-                "state",   // 30
-                "@",       // 31
-                "r>",      // 32
-                "0=",      // 33
-                "and",     // 34
-                "0=",      // 35
-                "0branch", // 36
-                "28",      // 37 "$COMPILE" (44 - 37) 7 * 4 => 28
-                "r>",      // 48
+                "state",   // 33
+                "@",       // 34
+                "r>",      // 35
+                "0=",      // 36
+                "and",     // 37
+                "0=",      // 38
                 "0branch", // 39
-                "8",       // 40 "$EXEC_NON_LIT" (42 - 40) 2 * 4 => 8
-                "exit",    // 41
+                "28",      // 40 "$COMPILE" (47 - 40) 7 * 4 => 28
+                "r>",      // 41
+                "0branch", // 42
+                "8",       // 43 "$EXEC_NON_LIT" (45 - 43) 2 * 4 => 8
+                "exit",    // 44
                 // $EXEC_NON_LIT:
-                "execute", // 42
-                "exit",    // 43
+                "execute", // 45
+                "exit",    // 46
                 // $COMPILE:
-                "r>",      // 44
-                "0branch", // 45
-                "16",      // 46 "$COMPILE2" (50 - 46) 4 * 4 => 16
-                "'",       // 47
-                "lit",     // 48
-                ",",       // 49
+                "r>",      // 47
+                "0branch", // 48
+                "16",      // 49 "$COMPILE2" (53 - 49) 4 * 4 => 16
+                "lit",     // 50
+                "lit",     // 51
+                ",",       // 52
                 // $COMPILE2:
-                ",",    // 50
-                "exit", // 51
+                ",",    // 53
+                "exit", // 54
                 // $PARSE_ERROR:
-                "rdrop", // 52  dropping no longer needed temporary values
-                "rdrop", // 53
-                "lit",   // 54
-                "63",    // 55
-                "emit",  // 56
-                "exit",  // quit here would be better, but how?
+                "rdrop",       // 55  dropping no longer needed temporary values
+                "rdrop",       // 56
+                "drop",        // 57 number's result
+                "(undefined)", // 58
+                "exit",        // 59 quit here would be better, but how?
+                // $COMPILE_ONLY_ERROR:
+                "rdrop", // 60
+                "drop",  // 61 the word
+                "exit",  // 62
             ],
         );
         self.colon_def(
@@ -746,6 +772,18 @@ impl ForthVM {
             ],
         );
 
+        self.init_task_words();
+        self.init_channel_words();
+        self.init_trace_words();
+        self.init_profiler_words();
+        self.init_backtrace_words();
+        self.init_coverage_words();
+        self.init_tester_words();
+        self.init_compiler_words();
+        self.init_source_words();
+        self.init_heap_words();
+        self.init_wordlist_words();
+
         #[cfg(feature = "fileio")]
         self.init_fileio_words();
     }
@@ -781,12 +819,12 @@ impl ForthVM {
 
 fn print_top_value(vm: &mut VM) {
     let value = vm.pop_i32();
-    print!("{value} ");
+    out_stream_write(&format!("{value} "));
 }
 
 fn emit_char(vm: &mut VM) {
     let value = vm.pop_i32() as u8 as char;
-    print!("{value}");
+    out_stream_write(&value.to_string());
 }
 
 // TODO: reimplement as builtin
@@ -830,6 +868,10 @@ fn find(vm: &mut VM) {
 }
 
 fn _find(vm: &VM, len: i32, ptr: i32) -> i32 {
+    // headers of :noname words have no name
+    if len == 0 {
+        return 0;
+    }
     let mut w = vm.read_i32(mmap::LATEST);
 
     while w != 0 {
@@ -853,40 +895,87 @@ fn number(vm: &mut VM) {
 }
 
 fn _number(vm: &mut VM, len: i32, caddr: i32) -> (i32, i32) {
-    if len == 0 {
-        return (0, 0);
+    let text: Vec<u8> = (0..len).map(|i| vm.read_u8((caddr + i) as usize)).collect();
+    let base = vm.read_i32(mmap::BASE) as u32;
+
+    match parse_number(&text, base) {
+        Ok(n) => (n, 0),
+        Err(unconverted) => (0, unconverted as i32),
     }
-    let mut n: i32 = 1; // bytes read
-    let mut c = vm.read_u8(caddr as usize) as char;
-    let is_negative = if c == '-' {
-        if len == 1 {
-            return (0, 0);
-        }
-        c = vm.read_u8((caddr + n) as usize) as char;
-        n += 1;
-        true
-    } else {
-        false
-    };
+}
 
-    let base = vm.read_i32(mmap::BASE);
+/// parses `-12`, `$ff`, `#10`, `%1010` or `'a'`. digits without prefix are in `base`.
+/// values have to fit a 32 bit cell, `$ffffffff` is -1.
+/// Err holds the number of unconverted chars
+pub(crate) fn parse_number(text: &[u8], base: u32) -> Result<i32, usize> {
+    // empty text and a lone `-` were always read as 0
+    if text.is_empty() || text == b"-" {
+        return Ok(0);
+    }
+    if let [b'\'', c, b'\''] = text {
+        return Ok(*c as i32);
+    }
 
-    let mut result = 0;
-    while n <= len {
-        if let Some(digit) = c.to_digit(base as u32) {
-            result *= base;
-            result += digit as i32;
-        } else {
-            return (0, len - n + 1);
+    let (base, rest) = match text[0] {
+        b'$' => (16, &text[1..]),
+        b'#' => (10, &text[1..]),
+        b'%' => (2, &text[1..]),
+        _ => (base, text),
+    };
+    let (is_negative, digits) = match rest.first() {
+        Some(b'-') => (true, &rest[1..]),
+        _ => (false, rest),
+    };
+    if digits.is_empty() || !(2..=36).contains(&base) {
+        return Err(text.len());
+    }
+
+    let limit = if is_negative {
+        1 << 31
+    } else {
+        u32::MAX as u64
+    };
+    let mut result: u64 = 0;
+    for (i, c) in digits.iter().enumerate() {
+        let unconverted = digits.len() - i;
+        let digit = (*c as char).to_digit(base).ok_or(unconverted)?;
+        result = result * base as u64 + digit as u64;
+        if result > limit {
+            return Err(unconverted);
         }
-        c = vm.read_u8((caddr + n) as usize) as char;
-        n += 1;
     }
 
-    if is_negative {
-        result = -result;
+    let result = result as u32;
+    Ok(if is_negative {
+        result.wrapping_neg() as i32
+    } else {
+        result as i32
+    })
+}
+
+// ( ud1 c-addr1 u1 -- ud2 c-addr2 u2 )
+fn to_number(vm: &mut VM) {
+    let mut len = vm.pop_i32();
+    let mut addr = vm.pop_i32();
+    let hi = vm.pop_i32() as u32 as u64;
+    let lo = vm.pop_i32() as u32 as u64;
+    let base = vm.read_i32(mmap::BASE) as u32;
+
+    let mut ud = (hi << 32) | lo;
+    while len > 0 {
+        let c = vm.read_u8(addr as usize) as char;
+        let Some(digit) = c.to_digit(base.clamp(2, 36)) else {
+            break;
+        };
+        ud = ud.wrapping_mul(base as u64).wrapping_add(digit as u64);
+        addr += 1;
+        len -= 1;
     }
-    (result, 0)
+
+    vm.push_i32(ud as u32 as i32);
+    vm.push_i32((ud >> 32) as u32 as i32);
+    vm.push_i32(addr);
+    vm.push_i32(len);
 }
 
 fn key(vm: &mut VM) {
@@ -897,6 +986,9 @@ fn key(vm: &mut VM) {
 fn _key(vm: &mut VM) -> char {
     if let Some(c) = read_next_char(vm) {
         c as char
+    } else if vm.read_i32(mmap::SOURCE_ID) == EVALUATE_SOURCE {
+        // the evaluated string ends like a line
+        '\n'
     } else {
         let mut line = String::new();
 
@@ -912,6 +1004,7 @@ fn _key(vm: &mut VM) -> char {
         }
 
         fill_input_buffer(vm, &line);
+        vm.write_i32(1, mmap::SOURCE_STREAM);
         read_next_char(vm).unwrap() as char
     }
 }
@@ -928,8 +1021,11 @@ fn word(vm: &mut VM) {
     vm.push_i32(len);
 }
 
-fn _word(vm: &mut VM) -> (i32, i32) {
+pub(crate) fn _word(vm: &mut VM) -> (i32, i32) {
     let mut c = skip_white_space(vm);
+    // >in is already past the first char
+    let column = vm.read_i32(mmap::TO_IN);
+    vm.write_i32(column, mmap::WORD_COLUMN);
 
     let mut len = 0;
     let buf_ptr = vm.read_i32(mmap::IN_STREAM);
@@ -939,6 +1035,7 @@ fn _word(vm: &mut VM) -> (i32, i32) {
         c = _key(vm);
     }
     // _tell(vm, buf_ptr, len);
+    vm.write_i32(len, mmap::WORD_LEN);
     (len, buf_ptr)
 }
 
@@ -950,7 +1047,7 @@ fn tell(vm: &mut VM) {
 
 fn _tell(vm: &VM, ptr: i32, len: i32) {
     let s = make_string(vm, len, ptr);
-    print!("{s}");
+    out_stream_write(&s);
 }
 
 fn read_char(vm: &mut VM) {
@@ -963,6 +1060,10 @@ fn skip_white_space(vm: &mut VM) -> char {
     let mut c = _key(vm);
     loop {
         while c.is_ascii_whitespace() {
+            if evaluate_done(vm) {
+                // empty word
+                return c;
+            }
             c = _key(vm);
         }
 
@@ -977,6 +1078,11 @@ fn skip_white_space(vm: &mut VM) -> char {
     }
 }
 
+fn evaluate_done(vm: &VM) -> bool {
+    vm.read_i32(mmap::SOURCE_ID) == EVALUATE_SOURCE
+        && vm.read_i32(mmap::TO_IN) >= vm.read_i32(mmap::SOURCE_LEN)
+}
+
 fn make_string(vm: &VM, len: i32, ptr: i32) -> String {
     let mut s = String::new();
 
@@ -999,7 +1105,7 @@ mod fileio {
     use toyvm::VM;
 
     use crate::{
-        in_stream_from_file,
+        in_stream_from_named_file,
         init_dictionary::{_word, make_string},
     };
 
@@ -1022,7 +1128,7 @@ mod fileio {
 
         match File::open(&path) {
             Ok(file) => {
-                in_stream_from_file(file);
+                in_stream_from_named_file(file, &path);
             }
             Err(err) => {
                 println!("could not open '{path}'\n {:?}", err);
@@ -1,12 +1,30 @@
 use std::{
     cell::RefCell,
+    fmt,
     fs::File,
     io::{BufRead, BufReader, IsTerminal},
 };
 
+/// position in forth source. line and column are 1 based, 0 if unknown
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
 pub struct InputStream {
     buf_read: Box<dyn BufRead>,
     is_terminal: bool,
+    name: String,
+    /// lines read so far
+    line: usize,
 }
 
 impl InputStream {
@@ -14,6 +32,8 @@ impl InputStream {
         InputStream {
             buf_read: Box::new(BufReader::new(f)),
             is_terminal: false,
+            name: "<file>".to_string(),
+            line: 0,
         }
     }
 
@@ -23,11 +43,23 @@ impl InputStream {
         InputStream {
             buf_read: Box::new(stdin.lock()),
             is_terminal,
+            name: "<stdin>".to_string(),
+            line: 0,
         }
     }
 
+    /// name used in source locations
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
     pub fn read_line(&mut self, s: &mut String) -> usize {
-        self.buf_read.read_line(s).unwrap_or_default()
+        let n = self.buf_read.read_line(s).unwrap_or_default();
+        if n > 0 {
+            self.line += 1;
+        }
+        n
     }
 
     pub fn is_terminal(&self) -> bool {
@@ -47,6 +79,10 @@ pub fn in_stream_from_file(file: File) {
     IN_STREAM.replace(Some(InputStream::from_file(file)));
 }
 
+pub fn in_stream_from_named_file(file: File, name: &str) {
+    IN_STREAM.replace(Some(InputStream::from_file(file).with_name(name)));
+}
+
 pub fn in_stream_is_terminal() -> bool {
     let mut it = false;
 
@@ -69,3 +105,15 @@ pub fn in_stream_read_line(s: &mut String) -> usize {
     });
     n
 }
+
+/// name and line of the line read last from the input stream
+pub(crate) fn in_stream_location() -> SourceLocation {
+    IN_STREAM.with_borrow(|i| match i {
+        Some(in_stream) => SourceLocation {
+            file: in_stream.name.clone(),
+            line: in_stream.line,
+            column: 0,
+        },
+        None => SourceLocation::default(),
+    })
+}
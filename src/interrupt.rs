@@ -0,0 +1,33 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// stops a running forth word from another thread.
+/// the flag is checked before every NEXT dispatch of a checked run
+/// (`try_run_word`, `run_word_with_fuel`, `resume`). `run_word` takes
+/// the checked path while a handle is held
+#[derive(Clone, Default)]
+pub struct InterruptHandle {
+    flag: Arc<AtomicBool>,
+}
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// a handle was handed out or an interrupt is pending
+    pub(crate) fn is_watched(&self) -> bool {
+        Arc::strong_count(&self.flag) > 1 || self.is_interrupted()
+    }
+
+    /// returns the pending interrupt and clears it
+    pub(crate) fn take(&self) -> bool {
+        self.flag.swap(false, Ordering::Relaxed)
+    }
+}
@@ -1,14 +1,46 @@
+mod backtrace;
+mod channels;
+mod compiler;
+mod coverage;
+mod debugger;
+mod error;
 mod forthvm;
+mod heap;
+mod host;
 mod init_dictionary;
 mod input_stream;
+mod interrupt;
+mod memory;
+mod native;
+mod optimizer;
+mod output_stream;
+mod profiler;
+mod source;
+mod tasks;
+mod tester;
+mod trace;
+mod wordlist;
 
 #[cfg(test)]
 mod tests;
 
 pub const CELL: i32 = 4;
 
-pub use forthvm::{ForthVM, VmConfig};
+pub use backtrace::Frame;
+pub use channels::chan;
+pub use coverage::{BranchCoverage, CoverageReport, WordCoverage};
+pub use debugger::{Breakpoint, Debugger, StopEvent, StopReason};
+pub use error::{ForthError, Result};
+pub use forthvm::{ForthVM, RunStatus, VmConfig};
+pub use host::{FromStack, HostFn, ToStack};
 pub use input_stream::*;
+pub use interrupt::InterruptHandle;
+pub use output_stream::*;
+pub use profiler::ProfileEntry;
+pub use tasks::{TASK_STACK_CELLS, tcb};
+pub use tester::TestFailure;
+pub use trace::TraceEvent;
+pub use wordlist::{WordInfo, WordKind};
 
 use crate::forthvm::NEXT;
 
@@ -50,17 +82,49 @@ pub mod mmap {
 
     pub const COLD_START: usize = 68;
 
+    /// terminal input buffer. length byte followed by the line
     pub const INPUT_BUFFER: usize = 72;
-    pub const INPUT_BUFFER_IDX: usize = 76;
+    /// `>in`, offset of the next char in the current source
+    pub const TO_IN: usize = 76;
+    /// END opcode, ends `vm.run`
+    pub const HALT_CODE: usize = 80;
+    /// ip after a HOST_CALL that stopped `vm.run`, 0 if none
+    pub const HOST_RESUME: usize = 84;
+    /// thread cell used to return from a word into rust. holds HALT_XT
+    pub const HALT: usize = 88;
+    /// code field of the halt word. points to HALT_CODE
+    pub const HALT_XT: usize = 92;
+    /// task control block of the running task
+    pub const TASK: usize = 96;
+    /// is tracing on (1) or off (0)
+    pub const TRACE: usize = 100;
+    /// length of the word read last by `word`
+    pub const WORD_LEN: usize = 104;
+    /// column of the word read last by `word`
+    pub const WORD_COLUMN: usize = 108;
+    /// 1 while the source is a line read from the input stream,
+    /// 0 while the location set with `set_source_location` applies
+    pub const SOURCE_STREAM: usize = 112;
+    /// start of the current source
+    pub const SOURCE_ADDR: usize = 116;
+    /// length of the current source
+    pub const SOURCE_LEN: usize = 120;
+    /// 0 for user input, -1 while `evaluate` runs
+    pub const SOURCE_ID: usize = 124;
     /// docol code location
-    pub const DOCOL: usize = 80;
-    /// start of forth dictionary
-    pub const DICT: usize = DOCOL + 64;
+    pub const DOCOL: usize = 128;
+    /// unused bytes between docol and the stacks. the parameter stack grows
+    /// down towards docol, an overflow runs into this gap first
+    pub const STACK_GUARD: usize = 256;
+    /// start of the stacks, followed by the heap and the forth dictionary
+    pub const STACKS: usize = DOCOL + 64 + STACK_GUARD;
 }
 pub const MAX_WORD_LEN: usize = 13;
 
 pub const LEN_MASK: u8 = 0x1f;
 pub const HIDDEN: u8 = 0x20;
+/// interpreting the word is an error
+pub const COMPILE_ONLY: u8 = 0x40;
 pub const IMMEDIATE: u8 = 0x80;
 
 pub const TRUE: i32 = 0x1;
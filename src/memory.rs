@@ -0,0 +1,72 @@
+use toyvm::VM;
+
+use crate::{
+    ForthVM,
+    error::{ForthError, Result},
+    forthvm::forth_opcodes,
+    mmap,
+};
+
+/// bytes kept free above `here`, primitives like `,` write there
+/// before the next check
+pub(crate) const HEADROOM: usize = 1024;
+
+impl ForthVM {
+    /// first byte of the dictionary, after the stacks and the heap
+    pub fn dict_start(&self) -> i32 {
+        self.dict_start
+    }
+
+    /// current size of the vm memory
+    pub fn memory_size(&self) -> usize {
+        self.vm.memory_ref().len()
+    }
+
+    /// the memory does not grow past `VmConfig::max_memory_size_bytes`
+    pub fn memory_limit(&self) -> usize {
+        self.memory_limit
+    }
+
+    /// grows the memory so `here` is followed by `HEADROOM` bytes.
+    /// false if that would pass the memory limit
+    pub(crate) fn reserve_dictionary(&mut self, here: i32) -> bool {
+        let needed = here.max(0) as usize + HEADROOM;
+        let size = self.memory_size();
+        if needed <= size {
+            return true;
+        }
+        if needed > self.memory_limit {
+            return false;
+        }
+        self.grow_memory(needed.max(size * 2).min(self.memory_limit));
+        true
+    }
+
+    /// called before every NEXT. reports a `here` moved past the memory limit
+    /// by a primitive or by `set_here`, and moves it back
+    pub(crate) fn check_dictionary(&mut self) -> Result<()> {
+        let here = self.here();
+        if !self.dictionary_full && here == self.checked_here {
+            return Ok(());
+        }
+        if !self.dictionary_full && self.reserve_dictionary(here) {
+            self.checked_here = here;
+            return Ok(());
+        }
+
+        self.dictionary_full = false;
+        self.vm.write_i32(self.checked_here, mmap::HERE);
+        self.abort();
+        Err(ForthError::DictionaryFull)
+    }
+
+    /// toyvm memory has a fixed size, so the vm is rebuilt around a larger copy.
+    /// the stacks are below the dictionary and keep their addresses
+    fn grow_memory(&mut self, size: usize) {
+        let mut memory = self.vm.memory_ref().to_vec();
+        memory.resize(size, 0);
+        let mut vm = VM::new(memory, self.vm_fns.clone(), mmap::DSP, mmap::CTOP);
+        vm.add_unknown_op_handler(&forth_opcodes);
+        self.vm = vm;
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use toyvm::opcode;
+
+use crate::{
+    ForthVM, LEN_MASK, MAX_WORD_LEN, align, docol,
+    forthvm::{DIV_MOD, NEXT, OVER, ROT},
+    mmap,
+    optimizer::RETURN_STACK_WORDS,
+};
+
+/// opcodes of builtins that are copied into native code.
+/// the code must not read IC or A0 and must end with its only NEXT.
+/// vm and host calls are left out, they may switch tasks or run forth code
+const INLINE_OPCODES: &[u8] = &[
+    opcode::ADD,
+    opcode::AND,
+    opcode::DEC,
+    opcode::DIV_S,
+    opcode::DROP,
+    opcode::DUP,
+    opcode::EQ,
+    opcode::EQZ,
+    opcode::GE_S,
+    opcode::GT_S,
+    opcode::GT_U,
+    opcode::I32_CONST,
+    opcode::I32_LOAD,
+    opcode::I32_LOAD_8,
+    opcode::I32_STORE,
+    opcode::I32_STORE_8,
+    opcode::INC,
+    opcode::JZI,
+    opcode::LE_S,
+    opcode::LT_S,
+    opcode::LT_U,
+    opcode::MAX,
+    opcode::MIN,
+    opcode::MOD_S,
+    opcode::MUL,
+    opcode::NOT,
+    opcode::OR,
+    opcode::SUB,
+    opcode::SWAP,
+    opcode::XOR,
+    opcode::ZERO,
+    OVER,
+    ROT,
+    DIV_MOD,
+];
+
+/// bytes of docol pushing IC on the return stack
+const PROLOGUE_LEN: usize = 28;
+
+/// translation of one threaded call
+enum Native {
+    /// builtin code without NEXT, or a literal
+    Code(Vec<u8>),
+    /// jump to the native address of a threaded address
+    Branch(i32),
+    ZeroBranch(i32),
+    /// sets IC to a continuation cell and jumps into xt. the word
+    /// returns to the continuation with its NEXT
+    Call(i32),
+}
+
+impl Native {
+    fn len(&self) -> i32 {
+        match self {
+            Native::Code(code) => code.len() as i32,
+            Native::Branch(_) => 6,
+            Native::ZeroBranch(_) => 5,
+            Native::Call(_) => 25,
+        }
+    }
+}
+
+fn i32_const(code: &mut Vec<u8>, value: i32) {
+    code.push(opcode::I32_CONST);
+    code.extend(value.to_ne_bytes());
+}
+
+impl ForthVM {
+    /// translate colon definitions finished by `;` or `colon_def` into toyvm bytecode.
+    /// native words run without NEXT between their calls, so the debugger, tracing
+    /// and interrupts only see the words they call. off by default
+    pub fn set_native_compile(&mut self, enabled: bool) {
+        self.optimizer.native = enabled;
+    }
+
+    pub fn is_native_compile(&self) -> bool {
+        self.optimizer.native
+    }
+
+    /// replaces the colon definition at `header`, which must be the latest word
+    /// and end at `here`, with a builtin of the same name and code field address.
+    /// returns false if the word keeps its threaded code
+    pub(crate) fn compile_native(&mut self, header: i32) -> bool {
+        let cfa = self.cfa(header);
+        let Some(ops) = self.decode(cfa + 4, self.here()) else {
+            return false;
+        };
+        let rstack_words = RETURN_STACK_WORDS.map(|name| self.xt_of(name));
+        let [lit, lit_add, lit_fetch, branch, zbranch, tail, exit] = [
+            "lit", "(lit+)", "(lit@)", "branch", "0branch", "(tail)", "exit",
+        ]
+        .map(|name| self.xt_of(name));
+
+        let mut natives = Vec::with_capacity(ops.len());
+        for op in &ops {
+            let native = match op.cells[..] {
+                [xt, ..] if rstack_words.contains(&xt) => return false,
+                [xt, n] if xt == lit => {
+                    let mut code = Vec::new();
+                    i32_const(&mut code, n);
+                    Native::Code(code)
+                }
+                [xt, n] if xt == lit_add => {
+                    let mut code = Vec::new();
+                    i32_const(&mut code, n);
+                    code.push(opcode::ADD);
+                    Native::Code(code)
+                }
+                [xt, addr] if xt == lit_fetch => {
+                    let mut code = Vec::new();
+                    i32_const(&mut code, addr);
+                    code.push(opcode::I32_LOAD);
+                    Native::Code(code)
+                }
+                [xt, offset] if xt == branch => Native::Branch(op.addr + 4 + offset),
+                [xt, offset] if xt == zbranch => Native::ZeroBranch(op.addr + 4 + offset),
+                // tail recursion loops to the first call
+                [xt, callee] if xt == tail && callee == cfa => Native::Branch(cfa + 4),
+                [xt, callee] if xt == tail => {
+                    let mut code = Vec::new();
+                    i32_const(&mut code, callee + 4);
+                    i32_const(&mut code, mmap::IC as i32);
+                    code.extend([opcode::I32_STORE, NEXT]);
+                    Native::Code(code)
+                }
+                // exit only touches the return stack and IC, NEXT included
+                [xt] if xt == exit => Native::Code(self.builtin_code(exit)),
+                [xt] => match self.inline_code(xt) {
+                    Some(code) => Native::Code(code),
+                    None => Native::Call(xt),
+                },
+                // litstring keeps its string in the threaded code
+                _ => return false,
+            };
+            natives.push(native);
+        }
+
+        // native address of every threaded address
+        let start = cfa + 4;
+        let mut addrs = HashMap::new();
+        let mut addr = start + PROLOGUE_LEN as i32;
+        for (op, native) in ops.iter().zip(&natives) {
+            addrs.insert(op.addr, addr);
+            addr += native.len();
+        }
+        addrs.insert(self.here(), addr);
+        let mut targets = natives.iter().filter_map(|native| match native {
+            Native::Branch(target) | Native::ZeroBranch(target) => Some(target),
+            _ => None,
+        });
+        if targets.any(|target| !addrs.contains_key(target)) {
+            return false;
+        }
+
+        // continuation cells follow the code, two cells per call
+        let continuations = align(addr);
+        let mut code = docol()[..PROLOGUE_LEN].to_vec();
+        let mut resumes = Vec::new();
+        for native in natives {
+            match native {
+                Native::Code(bytes) => code.extend(bytes),
+                Native::Branch(target) => {
+                    i32_const(&mut code, addrs[&target]);
+                    code.push(opcode::BR);
+                }
+                Native::ZeroBranch(target) => {
+                    code.push(opcode::BRZI);
+                    code.extend(addrs[&target].to_ne_bytes());
+                }
+                Native::Call(xt) => {
+                    let cell = continuations + resumes.len() as i32 * 8;
+                    i32_const(&mut code, cell);
+                    i32_const(&mut code, mmap::IC as i32);
+                    code.push(opcode::I32_STORE);
+                    // like execute
+                    i32_const(&mut code, xt);
+                    code.push(opcode::DUP);
+                    i32_const(&mut code, mmap::A0 as i32);
+                    code.extend([opcode::I32_STORE, opcode::I32_LOAD, opcode::BR]);
+                    resumes.push(start + code.len() as i32);
+                }
+            }
+        }
+        code.resize((continuations - start) as usize, 0);
+        for (i, resume) in resumes.into_iter().enumerate() {
+            // NEXT at resume: xt = cell + 4, ip = resume
+            let cell = continuations + i as i32 * 8;
+            code.extend((cell + 4).to_ne_bytes());
+            code.extend(resume.to_ne_bytes());
+        }
+
+        let link = self.read_i32(header);
+        let len_flags = self.read_u8(header + 4);
+        let len = ((len_flags & LEN_MASK) as usize).min(MAX_WORD_LEN);
+        let name = self.read_string(header + 5, len as i32);
+
+        self.rewind_here(header);
+        self.set_latest(link);
+        self.builtin_ex(&name, 0, &code);
+        self.write_u8(len_flags, header + 4);
+        true
+    }
+
+    /// code of a builtin up to and including its NEXT
+    fn builtin_code(&self, xt: i32) -> Vec<u8> {
+        let start = self.read_i32(xt);
+        let mut code = Vec::new();
+        let mut ip = start;
+        loop {
+            let op = self.read_u8(ip);
+            let len = if matches!(op, opcode::I32_CONST | opcode::JZI | opcode::BRZI) {
+                5
+            } else {
+                1
+            };
+            code.extend((ip..ip + len).map(|i| self.read_u8(i)));
+            ip += len;
+            if op == NEXT || op == opcode::END {
+                return code;
+            }
+        }
+    }
+
+    /// code of a builtin that can be copied, without its NEXT
+    fn inline_code(&self, xt: i32) -> Option<Vec<u8>> {
+        if self.read_i32(xt) != xt + 4 {
+            return None;
+        }
+        let mut code = Vec::new();
+        let mut ip = xt + 4;
+        loop {
+            let op = self.read_u8(ip);
+            if op == NEXT {
+                return Some(code);
+            }
+            if !INLINE_OPCODES.contains(&op) {
+                return None;
+            }
+            code.push(op);
+            ip += 1;
+            if op == opcode::I32_CONST || op == opcode::JZI {
+                let operand = self.read_i32(ip);
+                if op == opcode::I32_CONST
+                    && (operand == mmap::IC as i32 || operand == mmap::A0 as i32)
+                {
+                    return None;
+                }
+                code.extend(operand.to_ne_bytes());
+                ip += 4;
+            }
+        }
+    }
+}
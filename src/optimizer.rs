@@ -0,0 +1,327 @@
+use std::collections::{HashMap, HashSet};
+
+use toyvm::opcode;
+
+use crate::{
+    ForthVM, IMMEDIATE, align,
+    forthvm::{LIT_ADD, LIT_FETCH, NEXT},
+    mmap,
+};
+
+/// words the optimizer recognizes. looked up once, so later redefinitions
+/// of e.g. `+` are not fused
+const PRIMITIVES: &[&str] = &[
+    "lit",
+    "branch",
+    "0branch",
+    "litstring",
+    "+",
+    "-",
+    "@",
+    "swap",
+    "drop",
+    "over",
+    "r>",
+    "nip",
+    "2dup",
+    "rdrop",
+    "(lit+)",
+    "(lit@)",
+    "(tail)",
+    "exit",
+    ">r",
+    "rsp@",
+    "rsp!",
+];
+
+/// words using the return stack frames of their callers.
+/// they are not tail called and keep their threaded code
+pub(crate) const RETURN_STACK_WORDS: [&str; 5] = [">r", "r>", "rdrop", "rsp@", "rsp!"];
+
+/// sequences replaced by a single word
+const FUSED: &[(&[&str], &str)] = &[
+    (&["swap", "drop"], "nip"),
+    (&["over", "over"], "2dup"),
+    (&["r>", "drop"], "rdrop"),
+];
+
+#[derive(Default)]
+pub(crate) struct Optimizer {
+    pub(crate) enabled: bool,
+    /// see `ForthVM::set_tail_calls`
+    pub(crate) tail_calls: bool,
+    /// see `ForthVM::set_native_compile`
+    pub(crate) native: bool,
+    /// words marked with `notail`
+    no_tail: HashSet<i32>,
+    xts: HashMap<&'static str, i32>,
+}
+
+/// one threaded call with its operands
+pub(crate) struct Op {
+    /// address before optimizing
+    pub(crate) addr: i32,
+    pub(crate) cells: Vec<i32>,
+}
+
+impl ForthVM {
+    /// called before `;` is defined
+    pub(crate) fn init_optimizer_words(&mut self) {
+        // ( a -- a+n ) n is the next cell
+        self.builtin("(lit+)", &[LIT_ADD, NEXT]);
+        // ( -- x ) x is stored at the address in the next cell
+        self.builtin("(lit@)", &[LIT_FETCH, NEXT]);
+        // ( -- ) continues in the body of the colon word in the next cell,
+        // without pushing a return address
+        let ic = mmap::IC.to_ne_bytes();
+        self.builtin(
+            "(tail)",
+            &[
+                opcode::I32_CONST,
+                ic[0],
+                ic[1],
+                ic[2],
+                ic[3],
+                opcode::I32_LOAD,
+                opcode::I32_LOAD,
+                opcode::I32_CONST,
+                4,
+                0,
+                0,
+                0,
+                opcode::ADD,
+                opcode::I32_CONST,
+                ic[0],
+                ic[1],
+                ic[2],
+                ic[3],
+                opcode::I32_STORE,
+                NEXT,
+            ],
+        );
+        self.register_native("(optimize)", |forth| {
+            forth.finish_colon_def(forth.latest());
+        });
+        // the word being defined neither makes nor gets tail calls
+        self.register_native("notail", |forth| {
+            let xt = forth.cfa(forth.latest());
+            forth.optimizer.no_tail.insert(xt);
+        });
+        let flags = self.latest() + 4;
+        self.write_u8(self.read_u8(flags) | IMMEDIATE, flags);
+
+        for name in PRIMITIVES {
+            let xt = self.xt_of(name);
+            self.optimizer.xts.insert(name, xt);
+        }
+    }
+
+    /// fuses common sequences of colon definitions finished by `;` or `colon_def`
+    /// into superinstructions. off by default
+    pub fn set_optimize(&mut self, enabled: bool) {
+        self.optimizer.enabled = enabled;
+    }
+
+    pub fn is_optimizing(&self) -> bool {
+        self.optimizer.enabled
+    }
+
+    /// turns a call of a colon word followed by `exit` into a jump, so tail
+    /// recursive words run in constant return stack space. words using the
+    /// return stack and words marked with `notail` are left alone.
+    /// off by default
+    pub fn set_tail_calls(&mut self, enabled: bool) {
+        self.optimizer.tail_calls = enabled;
+    }
+
+    pub fn is_tail_calling(&self) -> bool {
+        self.optimizer.tail_calls
+    }
+
+    /// runs the enabled passes on a colon definition ending at `here`
+    pub(crate) fn finish_colon_def(&mut self, header: i32) {
+        if self.optimizer.enabled || self.optimizer.tail_calls {
+            self.optimize_word(header);
+        }
+        if self.optimizer.native {
+            self.compile_native(header);
+        }
+    }
+
+    /// rewrites the body of the word at `header`, which must end at `here`.
+    /// bodies holding data or branching into the middle of an operand are left alone
+    pub(crate) fn optimize_word(&mut self, header: i32) {
+        if self.optimizer.xts.is_empty() {
+            return;
+        }
+        let start = self.cfa(header) + 4;
+        let end = self.here();
+        let Some(ops) = self.decode(start, end) else {
+            return;
+        };
+
+        let targets: HashSet<i32> = ops
+            .iter()
+            .filter(|op| self.is_branch(op.cells[0]))
+            .map(|op| op.addr + 4 + op.cells[1])
+            .collect();
+        let boundaries: HashSet<i32> = ops.iter().map(|op| op.addr).chain([end]).collect();
+        if !targets.is_subset(&boundaries) {
+            return;
+        }
+
+        let mut ops = if self.optimizer.enabled {
+            self.fuse(&ops, &targets)
+        } else {
+            ops
+        };
+        if self.optimizer.tail_calls {
+            ops = self.tail_calls(self.cfa(header), ops, &targets);
+        }
+
+        let mut moved = HashMap::new();
+        let mut addr = start;
+        for op in &ops {
+            moved.insert(op.addr, addr);
+            addr += op.cells.len() as i32 * 4;
+        }
+        moved.insert(end, addr);
+
+        let mut addr = start;
+        for op in ops {
+            let mut cells = op.cells;
+            if self.is_branch(cells[0]) {
+                cells[1] = moved[&(op.addr + 4 + cells[1])] - (addr + 4);
+            }
+            for cell in cells {
+                self.write_i32(cell, addr);
+                addr += 4;
+            }
+        }
+        self.rewind_here(addr);
+    }
+
+    fn primitive(&self, name: &str) -> i32 {
+        self.optimizer.xts.get(name).copied().unwrap_or(0)
+    }
+
+    fn is_branch(&self, xt: i32) -> bool {
+        xt == self.primitive("branch") || xt == self.primitive("0branch")
+    }
+
+    /// splits the cells from `start` to `end` into calls,
+    /// None if a cell is not an xt
+    pub(crate) fn decode(&self, start: i32, end: i32) -> Option<Vec<Op>> {
+        let mut xts = HashSet::new();
+        let mut idx = self.latest();
+        while idx != 0 {
+            xts.insert(self.cfa(idx));
+            idx = self.read_i32(idx);
+        }
+        let with_operand =
+            ["lit", "branch", "0branch", "(lit+)", "(lit@)"].map(|name| self.primitive(name));
+
+        let mut ops = Vec::new();
+        let mut addr = start;
+        while addr < end {
+            let xt = self.read_i32(addr);
+            if !xts.contains(&xt) {
+                return None;
+            }
+            let next = if with_operand.contains(&xt) {
+                addr + 8
+            } else if xt == self.primitive("litstring") {
+                align(addr + 8 + self.read_i32(addr + 4))
+            } else {
+                addr + 4
+            };
+            let cells = (addr..next.min(end)).step_by(4).map(|a| self.read_i32(a));
+            ops.push(Op {
+                addr,
+                cells: cells.collect(),
+            });
+            addr = next;
+        }
+        (addr == end).then_some(ops)
+    }
+
+    /// `xt exit` becomes `(tail) xt`, the exit is kept when a branch jumps to it
+    fn tail_calls(&self, own_xt: i32, ops: Vec<Op>, targets: &HashSet<i32>) -> Vec<Op> {
+        let [tail, exit] = ["(tail)", "exit"].map(|name| self.primitive(name));
+        let rstack_words = RETURN_STACK_WORDS.map(|name| self.primitive(name));
+        let no_tail = &self.optimizer.no_tail;
+        if no_tail.contains(&own_xt) || ops.iter().any(|op| rstack_words.contains(&op.cells[0])) {
+            return ops;
+        }
+
+        let mut result: Vec<Op> = Vec::with_capacity(ops.len());
+        for op in ops {
+            if let Some(call) = result.last_mut() {
+                let xt = call.cells[0];
+                if op.cells == [exit]
+                    && call.cells.len() == 1
+                    && self.read_i32(xt) == mmap::DOCOL as i32
+                    && !no_tail.contains(&xt)
+                {
+                    call.cells = vec![tail, xt];
+                    if !targets.contains(&op.addr) {
+                        continue;
+                    }
+                }
+            }
+            result.push(op);
+        }
+        result
+    }
+
+    /// a branch may only jump to the first call of a fused sequence
+    fn fuse(&self, ops: &[Op], targets: &HashSet<i32>) -> Vec<Op> {
+        let [lit, plus, minus, fetch, lit_add, lit_fetch] =
+            ["lit", "+", "-", "@", "(lit+)", "(lit@)"].map(|name| self.primitive(name));
+
+        let mut fused = Vec::with_capacity(ops.len());
+        let mut i = 0;
+        while i < ops.len() {
+            let op = &ops[i];
+            let next = ops
+                .get(i + 1)
+                .filter(|next| !targets.contains(&next.addr))
+                .map(|next| next.cells.as_slice());
+
+            let cells = match (op.cells.as_slice(), next) {
+                (&[xt, n], Some(&[next])) if xt == lit && next == plus => Some(vec![lit_add, n]),
+                (&[xt, n], Some(&[next])) if xt == lit && next == minus => {
+                    Some(vec![lit_add, n.wrapping_neg()])
+                }
+                (&[xt, addr], Some(&[next])) if xt == lit && next == fetch => {
+                    Some(vec![lit_fetch, addr])
+                }
+                (&[first], Some(&[second])) => FUSED
+                    .iter()
+                    .find(|(seq, _)| {
+                        first == self.primitive(seq[0]) && second == self.primitive(seq[1])
+                    })
+                    .map(|(_, word)| vec![self.primitive(word)]),
+                _ => None,
+            };
+
+            match cells {
+                Some(cells) => {
+                    fused.push(Op {
+                        addr: op.addr,
+                        cells,
+                    });
+                    i += 2;
+                }
+                None => {
+                    fused.push(Op {
+                        addr: op.addr,
+                        cells: op.cells.clone(),
+                    });
+                    i += 1;
+                }
+            }
+        }
+        fused
+    }
+}
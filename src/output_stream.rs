@@ -0,0 +1,30 @@
+use std::cell::RefCell;
+
+thread_local! {
+    /// Some while output of forth words is captured instead of printed
+    pub static OUT_STREAM: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// collect output of `.`, `emit`, `tell` etc. instead of printing it to stdout
+pub fn out_stream_capture() {
+    OUT_STREAM.replace(Some(String::new()));
+}
+
+/// print to stdout again. returns output that was not taken yet
+pub fn out_stream_to_stdout() -> String {
+    OUT_STREAM.replace(None).unwrap_or_default()
+}
+
+/// returns and clears captured output
+pub fn out_stream_take() -> String {
+    OUT_STREAM.with_borrow_mut(|o| o.as_mut().map(std::mem::take).unwrap_or_default())
+}
+
+pub fn out_stream_write(s: &str) {
+    OUT_STREAM.with_borrow_mut(|o| match o {
+        Some(captured) => captured.push_str(s),
+        None => {
+            print!("{s}");
+        }
+    });
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::{ForthVM, mmap, output_stream::out_stream_write};
+
+/// counters of one word
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileEntry {
+    pub name: String,
+    pub xt: i32,
+    /// how often NEXT dispatched the word
+    pub calls: u64,
+    /// vm instructions from entering the word until its `exit`, callees included.
+    /// same as `exclusive` for builtins
+    pub inclusive: u64,
+    /// vm instructions spent in the word itself (DOCOL for colon definitions)
+    pub exclusive: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct Profiler {
+    entries: HashMap<i32, ProfileEntry>,
+    /// executed vm instructions
+    steps: u64,
+    /// word that owns the instructions since the last dispatch, when it was dispatched
+    /// and if it is a colon definition
+    current: Option<(i32, u64, bool)>,
+    /// colon definitions entered through DOCOL that did not `exit` yet
+    frames: Vec<(i32, u64)>,
+    exit_xt: i32,
+}
+
+impl Profiler {
+    fn entry(&mut self, xt: i32) -> &mut ProfileEntry {
+        self.entries.entry(xt).or_insert_with(|| ProfileEntry {
+            xt,
+            ..Default::default()
+        })
+    }
+
+    fn flush_current(&mut self) {
+        if let Some((xt, since, is_colon)) = self.current.take() {
+            let cost = self.steps - since;
+            let entry = self.entry(xt);
+            entry.exclusive += cost;
+            if !is_colon {
+                entry.inclusive += cost;
+            }
+
+            // the colon definition ends after its exit ran
+            if xt == self.exit_xt {
+                self.close_frame();
+            }
+        }
+    }
+
+    fn close_frame(&mut self) {
+        if let Some((xt, since)) = self.frames.pop() {
+            let cost = self.steps - since;
+            self.entry(xt).inclusive += cost;
+        }
+    }
+}
+
+impl ForthVM {
+    pub(crate) fn init_profiler_words(&mut self) {
+        self.register_native(".profile", |forth| forth.print_profile(10));
+    }
+
+    /// starts counting dispatches and instructions per word. clears earlier results
+    pub fn start_profiling(&mut self) {
+        let exit_xt = self.find("exit").map(|w| self.cfa(w)).unwrap_or(0);
+        self.profiler = Some(Profiler {
+            exit_xt,
+            ..Default::default()
+        });
+    }
+
+    /// stops profiling and returns the results
+    pub fn stop_profiling(&mut self) -> Vec<ProfileEntry> {
+        let report = self.profile_report();
+        self.profiler = None;
+        report
+    }
+
+    pub fn is_profiling(&self) -> bool {
+        self.profiler.is_some()
+    }
+
+    /// profiled words, most exclusive instructions first
+    pub fn profile_report(&self) -> Vec<ProfileEntry> {
+        let Some(profiler) = self.profiler.as_ref() else {
+            return Vec::new();
+        };
+
+        let mut report: Vec<ProfileEntry> = profiler
+            .entries
+            .values()
+            .map(|entry| ProfileEntry {
+                name: self.xt_name(entry.xt),
+                ..entry.clone()
+            })
+            .collect();
+        report.sort_by(|a, b| {
+            b.exclusive
+                .cmp(&a.exclusive)
+                .then(b.calls.cmp(&a.calls))
+                .then(a.name.cmp(&b.name))
+        });
+        report
+    }
+
+    /// prints the `n` hottest words
+    pub fn print_profile(&self, n: usize) {
+        let mut s = format!(
+            "{:<16}{:>10}{:>12}{:>12}\n",
+            "word", "calls", "inclusive", "exclusive"
+        );
+        for entry in self.profile_report().iter().take(n) {
+            s += &format!(
+                "{:<16}{:>10}{:>12}{:>12}\n",
+                entry.name, entry.calls, entry.inclusive, entry.exclusive
+            );
+        }
+        out_stream_write(&s);
+    }
+
+    pub(crate) fn profile_step(&mut self) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.steps += 1;
+        }
+    }
+
+    pub(crate) fn profile_next(&mut self) {
+        if self.profiler.is_none() {
+            return;
+        }
+
+        let ic = self.read_i32(mmap::IC as i32);
+        let xt = self.read_i32(ic);
+        if xt == mmap::HALT_XT as i32 {
+            return;
+        }
+        let is_colon = self.read_i32(xt) == mmap::DOCOL as i32;
+
+        let Some(profiler) = self.profiler.as_mut() else {
+            return;
+        };
+
+        profiler.flush_current();
+
+        profiler.entry(xt).calls += 1;
+        if is_colon {
+            profiler.frames.push((xt, profiler.steps));
+        }
+        profiler.current = Some((xt, profiler.steps, is_colon));
+    }
+
+    pub(crate) fn profile_depth(&self) -> usize {
+        self.profiler.as_ref().map_or(0, |p| p.frames.len())
+    }
+
+    /// closes words left open by a run that ended with `bye`
+    pub(crate) fn profile_unwind(&mut self, depth: usize) {
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.flush_current();
+            while profiler.frames.len() > depth {
+                profiler.close_frame();
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use toyvm::opcode;
+
+use crate::{
+    FALSE, ForthVM, TRUE, align,
+    error::{ForthError, Result},
+    forthvm::{NEXT, fill_input_buffer},
+    input_stream::in_stream_read_line,
+    mmap,
+};
+
+/// `source-id` while `evaluate` runs
+pub(crate) const EVALUATE_SOURCE: i32 = -1;
+
+/// bytes after the stacks for the sources of `ForthVM::evaluate`,
+/// shared by nested calls
+pub(crate) const EVALUATE_BUFFER: usize = 0x1000;
+
+/// cells pushed by `save-input`
+const INPUT_SPEC_CELLS: i32 = 4;
+
+impl ForthVM {
+    pub(crate) fn init_source_words(&mut self) {
+        let to_in = mmap::TO_IN.to_ne_bytes();
+        self.builtin(
+            ">in",
+            &[
+                opcode::I32_CONST,
+                to_in[0],
+                to_in[1],
+                to_in[2],
+                to_in[3],
+                NEXT,
+            ],
+        );
+
+        // ( -- c-addr u )
+        self.register_native("source", |forth| {
+            let [addr, len, ..] = forth.save_input();
+            forth.push_i32(addr);
+            forth.push_i32(len);
+        });
+        // ( -- 0 | -1 )
+        self.register_native("source-id", |forth| {
+            let [.., id] = forth.save_input();
+            forth.push_i32(id);
+        });
+        // ( -- flag ) reads the next line of the input stream
+        self.register_native("refill", |forth| {
+            let [.., id] = forth.save_input();
+            let mut line = String::new();
+            let ok = id != EVALUATE_SOURCE && in_stream_read_line(&mut line) > 0;
+            if ok {
+                fill_input_buffer(&mut forth.vm, &line);
+                forth.write_i32(1, mmap::SOURCE_STREAM as i32);
+            }
+            forth.push_i32(if ok { TRUE } else { FALSE });
+        });
+        // ( char "ccc<char>" -- c-addr u )
+        self.register_native("parse", |forth| {
+            let delimiter = forth.pop_i32() as u8;
+            let (addr, len) = forth.parse_until(|c| c == delimiter);
+            forth.push_i32(addr);
+            forth.push_i32(len);
+        });
+        // ( "<spaces>name<space>" -- c-addr u )
+        self.register_native("parse-name", |forth| {
+            let [source, len, mut to_in, _] = forth.save_input();
+            while to_in < len && forth.read_u8(source + to_in).is_ascii_whitespace() {
+                to_in += 1;
+            }
+            forth.write_i32(to_in, mmap::TO_IN as i32);
+
+            let (addr, len) = forth.parse_until(|c| c.is_ascii_whitespace());
+            forth.push_i32(addr);
+            forth.push_i32(len);
+        });
+        // ( -- x1 x2 x3 x4 4 )
+        self.register_native("save-input", |forth| {
+            for x in forth.save_input() {
+                forth.push_i32(x);
+            }
+            forth.push_i32(INPUT_SPEC_CELLS);
+        });
+        // ( x1 .. xn n -- flag ) false on success
+        self.register_native("restore-input", |forth| {
+            let n = forth.pop_i32();
+            let mut input = [0; INPUT_SPEC_CELLS as usize];
+            for i in (0..n).rev() {
+                let x = forth.pop_i32();
+                if let Some(cell) = input.get_mut(i as usize) {
+                    *cell = x;
+                }
+            }
+            let ok = n == INPUT_SPEC_CELLS;
+            if ok {
+                forth.restore_input(input);
+            }
+            forth.push_i32(if ok { FALSE } else { TRUE });
+        });
+        // ( i*x c-addr u -- j*x )
+        self.register_native("evaluate", |forth| {
+            let len = forth.pop_i32();
+            let addr = forth.pop_i32();
+
+            let input = forth.save_input();
+            forth.restore_input([addr, len, 0, EVALUATE_SOURCE]);
+            let result = forth.interpret_source();
+            forth.restore_input(input);
+
+            if let Err(err) = result {
+                forth.report_error(&format!("evaluate: {err}"));
+            }
+        });
+    }
+
+    /// interprets `source` like `evaluate`. values stay on the stack,
+    /// the previous input is restored afterwards.
+    /// the source is copied to a buffer after the stacks, longer sources fail
+    pub fn evaluate(&mut self, source: &str) -> Result<()> {
+        let addr = self.evaluate_top;
+        if source.len() > (self.evaluate_end - addr) as usize {
+            return Err(ForthError::SourceTooLong(source.len()));
+        }
+        self.vm.write(addr as usize, source.as_bytes());
+        self.evaluate_top = align(addr + source.len() as i32);
+
+        let input = self.save_input();
+        self.restore_input([addr, source.len() as i32, 0, EVALUATE_SOURCE]);
+        let result = self.interpret_source();
+        self.restore_input(input);
+
+        self.evaluate_top = addr;
+        result
+    }
+
+    /// runs `interpret` until the current source holds no more words
+    pub(crate) fn interpret_source(&mut self) -> Result<()> {
+        let interpret = self.xt_of("interpret");
+        while self.input_pending() {
+            self.execute_xt(interpret)?;
+        }
+        Ok(())
+    }
+
+    /// source address, source length, >in and source-id
+    pub(crate) fn save_input(&self) -> [i32; 4] {
+        [
+            mmap::SOURCE_ADDR,
+            mmap::SOURCE_LEN,
+            mmap::TO_IN,
+            mmap::SOURCE_ID,
+        ]
+        .map(|cell| self.read_i32(cell as i32))
+    }
+
+    pub(crate) fn restore_input(&mut self, input: [i32; 4]) {
+        let cells = [
+            mmap::SOURCE_ADDR,
+            mmap::SOURCE_LEN,
+            mmap::TO_IN,
+            mmap::SOURCE_ID,
+        ];
+        for (cell, value) in cells.into_iter().zip(input) {
+            self.write_i32(value, cell as i32);
+        }
+    }
+
+    /// ( -- c-addr u ) of the text from >in up to the delimiter,
+    /// >in is moved past the delimiter
+    fn parse_until(&mut self, is_delimiter: impl Fn(u8) -> bool) -> (i32, i32) {
+        let [source, len, to_in, _] = self.save_input();
+        let mut end = to_in;
+        while end < len && !is_delimiter(self.read_u8(source + end)) {
+            end += 1;
+        }
+        self.write_i32((end + 1).min(len), mmap::TO_IN as i32);
+        (source + to_in, end - to_in)
+    }
+}
@@ -0,0 +1,180 @@
+use toyvm::{VM, opcode};
+
+use crate::{CELL, ForthVM, error::Result, forthvm::NEXT, init_dictionary::_word, mmap};
+
+/// cells reserved for the parameter and for the return stack of a task created with `task`
+pub const TASK_STACK_CELLS: i32 = 64;
+
+/// task control block layout. tasks are linked in a ring, `pause` walks it
+pub mod tcb {
+    /// next task
+    pub const LINK: i32 = 0;
+    /// AWAKE or ASLEEP
+    pub const STATUS: i32 = 4;
+    pub const DSP: i32 = 8;
+    pub const S0: i32 = 12;
+    pub const RSP: i32 = 16;
+    pub const R0: i32 = 20;
+    pub const IC: i32 = 24;
+    pub const SIZE: i32 = 28;
+
+    pub const ASLEEP: i32 = 0;
+    pub const AWAKE: i32 = 1;
+}
+
+impl ForthVM {
+    pub(crate) fn init_task_words(&mut self) {
+        // the task that runs the interpreter. uses the stacks set up by from_config
+        let main = self.allot(tcb::SIZE).unwrap();
+        self.write_i32(main, main + tcb::LINK);
+        self.write_i32(tcb::AWAKE, main + tcb::STATUS);
+        self.write_i32(self.read_i32(mmap::S0 as i32), main + tcb::S0);
+        self.write_i32(self.read_i32(mmap::R0 as i32), main + tcb::R0);
+        self.write_i32(main, mmap::TASK as i32);
+        self.main_task = main;
+
+        let main = main.to_ne_bytes();
+        self.builtin(
+            "main-task",
+            &[opcode::I32_CONST, main[0], main[1], main[2], main[3], NEXT],
+        );
+
+        self.vm_call("pause", &pause);
+        self.vm_call("stop", &stop);
+        self.vm_call("wake", &wake);
+
+        // an activated task returns here when its code exits
+        let done = self.colon_def("(task-done)", &["stop", "branch", "-8"]);
+        self.task_done = self.cfa(done) + 4;
+        self.register_native("activate", activate);
+
+        // ( "name" -- )
+        self.register_native("task", |forth| {
+            let (len, ptr) = _word(&mut forth.vm);
+            let name = forth.read_string(ptr, len);
+            if let Err(err) = forth.new_task(&name) {
+                forth.report_error(&format!("task: {err}"));
+            }
+        });
+    }
+
+    /// creates a sleeping task with its own stacks and a word `name` that pushes its
+    /// task control block. returns the address of the task control block,
+    /// fails when the dictionary is full
+    pub fn new_task(&mut self, name: &str) -> Result<i32> {
+        let stack_bytes = TASK_STACK_CELLS * CELL;
+        let pstack = self.allot(2 * stack_bytes + tcb::SIZE)?;
+        let rstack = pstack + stack_bytes;
+        let task = rstack + stack_bytes;
+
+        let s0 = pstack + stack_bytes - CELL;
+        let r0 = rstack + stack_bytes - CELL;
+        self.write_i32(tcb::ASLEEP, task + tcb::STATUS);
+        self.write_i32(s0, task + tcb::DSP);
+        self.write_i32(s0, task + tcb::S0);
+        self.write_i32(r0, task + tcb::RSP);
+        self.write_i32(r0, task + tcb::R0);
+        // `wake` before `activate` stops the task again
+        self.write_i32(self.task_done, task + tcb::IC);
+
+        // insert after the current task
+        let current = self.current_task();
+        self.write_i32(self.read_i32(current + tcb::LINK), task + tcb::LINK);
+        self.write_i32(task, current + tcb::LINK);
+
+        let t = task.to_ne_bytes();
+        self.builtin(name, &[opcode::I32_CONST, t[0], t[1], t[2], t[3], NEXT]);
+
+        Ok(task)
+    }
+
+    pub fn current_task(&self) -> i32 {
+        self.read_i32(mmap::TASK as i32)
+    }
+
+    /// on abort. a task other than the main task is put to sleep and
+    /// the main task's stacks become the current ones again
+    pub(crate) fn stop_current_task(&mut self) {
+        let current = self.current_task();
+        if self.main_task == 0 || current == self.main_task {
+            return;
+        }
+        self.write_i32(tcb::ASLEEP, current + tcb::STATUS);
+        self.write_i32(self.task_done, current + tcb::IC);
+        let s0 = self.read_i32(current + tcb::S0);
+        let r0 = self.read_i32(current + tcb::R0);
+        self.write_i32(s0, current + tcb::DSP);
+        self.write_i32(r0, current + tcb::RSP);
+
+        let main = self.main_task;
+        self.write_i32(main, mmap::TASK as i32);
+        self.write_i32(self.read_i32(main + tcb::S0), mmap::S0 as i32);
+        self.write_i32(self.read_i32(main + tcb::R0), mmap::R0 as i32);
+    }
+}
+
+// ( task -- ) runs the rest of the calling word in `task` and returns from the calling word
+fn activate(forth: &mut ForthVM) {
+    let task = forth.pop_i32();
+    let done = forth.task_done;
+
+    let s0 = forth.read_i32(task + tcb::S0);
+    forth.write_i32(s0, task + tcb::DSP);
+
+    let r0 = forth.read_i32(task + tcb::R0);
+    forth.write_i32(done, r0);
+    forth.write_i32(r0 - CELL, task + tcb::RSP);
+
+    let ic = forth.read_i32(mmap::IC as i32);
+    forth.write_i32(ic, task + tcb::IC);
+    forth.write_i32(tcb::AWAKE, task + tcb::STATUS);
+
+    // exit
+    let rsp = forth.read_i32(mmap::RSP as i32) + CELL;
+    forth.write_i32(rsp, mmap::RSP as i32);
+    forth.write_i32(forth.read_i32(rsp), mmap::IC as i32);
+}
+
+// ( -- ) switch to the next awake task.
+// keeps running the current task when no other task is awake
+fn pause(vm: &mut VM) {
+    let current = vm.read_i32(mmap::TASK);
+    save_task(vm, current);
+
+    let mut next = vm.read_i32((current + tcb::LINK) as usize);
+    while next != current && vm.read_i32((next + tcb::STATUS) as usize) != tcb::AWAKE {
+        next = vm.read_i32((next + tcb::LINK) as usize);
+    }
+
+    restore_task(vm, next);
+}
+
+// ( -- ) put current task to sleep
+fn stop(vm: &mut VM) {
+    let current = vm.read_i32(mmap::TASK);
+    vm.write_i32(tcb::ASLEEP, (current + tcb::STATUS) as usize);
+    pause(vm);
+}
+
+// ( task -- )
+fn wake(vm: &mut VM) {
+    let task = vm.pop_i32();
+    vm.write_i32(tcb::AWAKE, (task + tcb::STATUS) as usize);
+}
+
+fn save_task(vm: &mut VM, task: i32) {
+    let task = task as usize;
+    vm.write_i32(vm.read_i32(mmap::DSP), task + tcb::DSP as usize);
+    vm.write_i32(vm.read_i32(mmap::RSP), task + tcb::RSP as usize);
+    vm.write_i32(vm.read_i32(mmap::IC), task + tcb::IC as usize);
+}
+
+fn restore_task(vm: &mut VM, task: i32) {
+    vm.write_i32(task, mmap::TASK);
+    let task = task as usize;
+    vm.write_i32(vm.read_i32(task + tcb::DSP as usize), mmap::DSP);
+    vm.write_i32(vm.read_i32(task + tcb::S0 as usize), mmap::S0);
+    vm.write_i32(vm.read_i32(task + tcb::RSP as usize), mmap::RSP);
+    vm.write_i32(vm.read_i32(task + tcb::R0 as usize), mmap::R0);
+    vm.write_i32(vm.read_i32(task + tcb::IC as usize), mmap::IC);
+}
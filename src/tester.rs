@@ -0,0 +1,146 @@
+use std::fmt;
+
+use crate::{
+    ForthVM,
+    error::{ForthError, Result},
+    input_stream::SourceLocation,
+    mmap,
+    output_stream::out_stream_write,
+};
+
+/// `t{ ... -> ... }t` that did not hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestFailure {
+    /// position of `t{`
+    pub location: SourceLocation,
+    /// text given to the last `testing`
+    pub section: String,
+    /// source line of the test
+    pub line: String,
+    pub message: String,
+}
+
+impl fmt::Display for TestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.location, self.message, self.line)?;
+        if !self.section.is_empty() {
+            write!(f, " (testing {})", self.section)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Tester {
+    /// stack depth and location at `t{`
+    start: Option<(i32, SourceLocation, String)>,
+    /// results between `t{` and `->`, bottom first
+    actual: Vec<i32>,
+    section: String,
+    failures: Vec<TestFailure>,
+}
+
+impl ForthVM {
+    pub(crate) fn init_tester_words(&mut self) {
+        self.register_native("t{", |forth| {
+            let line = forth.input_line();
+            forth.tester.start = Some((forth.pstack_depth(), forth.source_location(), line));
+        });
+        self.register_native("->", |forth| {
+            let start = forth.tester.start.as_ref().map_or(0, |s| s.0);
+            let n = forth.pstack_depth() - start;
+            if n < 0 {
+                forth.test_failed("stack underflow".to_string());
+            }
+            forth.tester.actual = forth.pop_results(n);
+        });
+        self.register_native("}t", |forth| {
+            let start = forth.tester.start.as_ref().map_or(0, |s| s.0);
+            let n = forth.pstack_depth() - start;
+            let expected = forth.pop_results(n);
+            let actual = std::mem::take(&mut forth.tester.actual);
+
+            if expected.len() != actual.len() {
+                forth.test_failed(format!(
+                    "wrong number of results, expected {expected:?} got {actual:?}"
+                ));
+            } else if expected != actual {
+                forth.test_failed(format!(
+                    "incorrect result, expected {expected:?} got {actual:?}"
+                ));
+            }
+            forth.tester.start = None;
+        });
+        self.register_native("testing", |forth| {
+            let mut section = String::new();
+            while let Some(c) = forth.read_next_char() {
+                if c == b'\n' {
+                    break;
+                }
+                section.push(c as char);
+            }
+            forth.tester.section = section.trim().to_string();
+        });
+        self.register_native("#errors", |forth| {
+            let n = forth.tester.failures.len() as i32;
+            forth.push_i32(n);
+        });
+    }
+
+    /// failed tests since the last `run_test_source` / `take_test_failures`
+    pub fn test_failures(&self) -> &[TestFailure] {
+        &self.tester.failures
+    }
+
+    pub fn take_test_failures(&mut self) -> Vec<TestFailure> {
+        std::mem::take(&mut self.tester.failures)
+    }
+
+    /// interprets `source` line by line and returns the failed `t{ -> }t` tests.
+    /// `name` is used in the source locations, values left by the source stay on the stack
+    pub fn run_test_source(&mut self, name: &str, source: &str) -> Result<Vec<TestFailure>> {
+        self.tester = Tester::default();
+
+        for (i, line) in source.lines().enumerate() {
+            self.set_source_location(name, i + 1);
+            self.fill_input_buffer(&format!("{line}\n"));
+            self.interpret_source()?;
+        }
+        Ok(self.take_test_failures())
+    }
+
+    pub fn run_test_file(&mut self, path: &str) -> Result<Vec<TestFailure>> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|err| ForthError::Io(format!("{path}: {err}")))?;
+        self.run_test_source(path, &source)
+    }
+
+    fn test_failed(&mut self, message: String) {
+        let (location, line) = match &self.tester.start {
+            Some((_, location, line)) => (location.clone(), line.clone()),
+            None => (self.source_location(), self.input_line()),
+        };
+        let failure = TestFailure {
+            location,
+            section: self.tester.section.clone(),
+            line,
+            message,
+        };
+        out_stream_write(&format!("\n{failure}\n"));
+        self.tester.failures.push(failure);
+    }
+
+    /// pops `n` values, bottom first
+    fn pop_results(&mut self, n: i32) -> Vec<i32> {
+        let mut values: Vec<i32> = (0..n).map(|_| self.pop_i32()).collect();
+        values.reverse();
+        values
+    }
+
+    /// current source, trimmed
+    fn input_line(&self) -> String {
+        let source = self.read_i32(mmap::SOURCE_ADDR as i32);
+        let len = self.read_i32(mmap::SOURCE_LEN as i32);
+        self.read_string(source, len).trim().to_string()
+    }
+}
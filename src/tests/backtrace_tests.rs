@@ -0,0 +1,58 @@
+use super::word_tests::create_vm;
+use crate::{ForthError, out_stream_capture, out_stream_take};
+
+#[test]
+fn test_backtrace_on_host_panic() {
+    let mut vm = create_vm();
+    vm.register("sensor@", |ch: i32| {
+        if ch > 3 {
+            panic!("no such channel");
+        }
+        ch
+    });
+    vm.colon_def("inner", &["sensor@", "exit"]);
+    vm.colon_def("middle", &["lit", "7", "inner", "exit"]);
+    let test = vm.colon_def("test_panic", &["middle", "bye"]);
+
+    let result = vm.try_run_word(test as usize);
+    assert_eq!(
+        result,
+        Err(ForthError::HostPanic("no such channel".to_string()))
+    );
+
+    let frames: Vec<_> = vm
+        .last_backtrace()
+        .iter()
+        .map(|frame| (frame.name.as_str(), frame.offset))
+        .collect();
+    assert_eq!(
+        frames,
+        vec![("inner", 4), ("middle", 12), ("test_panic", 4)]
+    );
+
+    // the host function can still be used
+    assert_eq!(vm.call("sensor@", &[2]), Ok(vec![2]));
+}
+
+#[test]
+fn test_backtrace_word() {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    vm.colon_def("show", &["lit", "1", ">r", "backtrace", "rdrop", "exit"]);
+    let test = vm.colon_def("test_show", &["show", "bye"]);
+    vm.run_word(test as usize);
+
+    assert_eq!(
+        out_stream_take(),
+        "\nbacktrace:\n  #0 show +16\n  #1 test_show +4\n"
+    );
+}
+
+#[test]
+fn test_word_at() {
+    let vm = create_vm();
+    let dup = vm.find("dup").unwrap();
+    assert_eq!(vm.word_at(vm.cfa(dup)), Some(dup));
+    assert_eq!(vm.word_at(0), None);
+}
@@ -0,0 +1,112 @@
+use toyvm::opcode;
+
+use super::word_tests::create_vm;
+use crate::{ForthError, ForthVM, forthvm::NEXT, out_stream_capture, out_stream_take};
+
+#[test]
+fn test_channel() {
+    let mut vm = create_vm();
+
+    vm.new_channel("ch", 2).unwrap();
+    vm.new_task("producer").unwrap();
+
+    // sends 1 2 3 4 5, blocks while the channel is full
+    vm.colon_def(
+        "produce",
+        &[
+            "producer", "activate", "lit", "1", "ch", "send", "lit", "2", "ch", "send", "lit", "3",
+            "ch", "send", "lit", "4", "ch", "send", "lit", "5", "ch", "send", "exit",
+        ],
+    );
+    let test_channel = vm.colon_def(
+        "test_channel",
+        &[
+            "produce", "ch", "receive", "ch", "receive", "+", "ch", "receive", "+", "ch",
+            "receive", "+", "ch", "receive", "+", "bye",
+        ],
+    );
+
+    vm.run_word(test_channel as usize);
+    assert_eq!(vm.pop_i32(), 15);
+    assert_eq!(vm.pstack_depth(), 0);
+    assert_eq!(vm.channel_drain("ch"), Ok(vec![]));
+}
+
+#[test]
+fn test_channel_host() {
+    let mut vm = create_vm();
+
+    vm.new_channel("ch", 3).unwrap();
+
+    assert_eq!(vm.channel_send("ch", 1), Ok(true));
+    assert_eq!(vm.channel_send("ch", 2), Ok(true));
+    assert_eq!(vm.channel_send("ch", 3), Ok(true));
+    assert_eq!(vm.channel_send("ch", 4), Ok(false), "full");
+
+    let test_try = vm.colon_def("test_try", &["ch", "try-receive", "bye"]);
+    vm.run_word(test_try as usize);
+    assert_eq!(vm.pop_i32(), 1, "true");
+    assert_eq!(vm.pop_i32(), 1);
+
+    let test_try_send = vm.colon_def("test_try_send", &["lit", "10", "ch", "try-send", "bye"]);
+    vm.run_word(test_try_send as usize);
+    assert_eq!(vm.pop_i32(), 1, "sent");
+
+    // wraps around the ring buffer
+    assert_eq!(vm.channel_drain("ch"), Ok(vec![2, 3, 10]));
+
+    vm.run_word(test_try as usize);
+    assert_eq!(vm.pop_i32(), 0, "empty");
+    assert_eq!(vm.pstack_depth(), 0);
+}
+
+#[test]
+fn test_channel_errors() {
+    let mut vm = create_vm();
+    let here = vm.here();
+    assert_eq!(vm.new_channel("ch", 0), Err(ForthError::InvalidCapacity(0)));
+    assert_eq!(
+        vm.new_channel("ch", -4),
+        Err(ForthError::InvalidCapacity(-4))
+    );
+    assert_eq!(vm.here(), here);
+
+    // other words are not run
+    vm.colon_def("seven", &["lit", "7", "exit"]);
+    assert_eq!(
+        vm.channel_send("seven", 1),
+        Err(ForthError::InvalidChannel("seven".to_string()))
+    );
+    assert_eq!(
+        vm.channel_drain("base"),
+        Err(ForthError::InvalidChannel("base".to_string()))
+    );
+    // constants that are not channel addresses, one of them outside the memory
+    let constant = |vm: &mut ForthVM, name: &str, x: i32| {
+        let x = x.to_ne_bytes();
+        vm.builtin(name, &[opcode::I32_CONST, x[0], x[1], x[2], x[3], NEXT]);
+    };
+    constant(&mut vm, "far", -8);
+    assert_eq!(
+        vm.channel_send("far", 1),
+        Err(ForthError::InvalidChannel("far".to_string()))
+    );
+    let ch = vm.new_channel("ch", 2).unwrap();
+    constant(&mut vm, "near", ch + 4);
+    assert_eq!(
+        vm.channel_drain("near"),
+        Err(ForthError::InvalidChannel("near".to_string()))
+    );
+    assert_eq!(
+        vm.channel_drain("nope"),
+        Err(ForthError::UndefinedWord("nope".to_string()))
+    );
+    assert_eq!(vm.pstack_depth(), 0);
+
+    out_stream_capture();
+    vm.fill_input_buffer("bad\n");
+    let test_bad = vm.colon_def("test_bad", &["lit", "-1", "channel", "bye"]);
+    vm.run_word(test_bad as usize);
+    assert!(out_stream_take().contains("invalid channel capacity -1"));
+    assert!(vm.find("bad").is_none());
+}
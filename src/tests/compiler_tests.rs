@@ -0,0 +1,60 @@
+use super::tester_tests::assert_forth_tests;
+use super::word_tests::create_vm;
+use crate::{HIDDEN, out_stream_capture, out_stream_take};
+
+#[test]
+fn test_compiler_fs() {
+    assert_forth_tests("src/tests/forth/compiler.fs");
+}
+
+#[test]
+fn test_compile_only() {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    let source = ": one 1 ; compile-only\none\n: two one one + ;\ntwo recurse\n";
+    vm.run_test_source("c.fs", source).unwrap();
+    assert_eq!(vm.pstack(), vec![2]);
+    assert_eq!(
+        out_stream_take(),
+        "\nc.fs:2:1: 'one' is compile-only\n\nc.fs:4:5: 'recurse' is compile-only\n"
+    );
+}
+
+#[test]
+fn test_undefined_in_compiler_word() {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    vm.run_test_source("u.fs", ": f ['] nope ;\n").unwrap();
+    assert_eq!(out_stream_take(), "\nu.fs:1:9: undefined word 'nope'\n");
+    assert!(!vm.is_compiling());
+}
+
+#[test]
+fn test_tick_undefined() {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    vm.run_test_source("t.fs", "1 ' nope execute 2\n3\n")
+        .unwrap();
+    assert_eq!(out_stream_take(), "\nt.fs:1:5: undefined word 'nope'\n");
+    // the rest of the line is skipped
+    assert_eq!(vm.pstack(), vec![1, 3]);
+
+    vm.run_test_source("t.fs", "' dup\n").unwrap();
+    assert_eq!(vm.pstack(), vec![1, 3, vm.cfa(vm.find("dup").unwrap())]);
+}
+
+#[test]
+fn test_noname_hidden() {
+    let mut vm = create_vm();
+    vm.run_test_source("t.fs", ":noname 7 ; execute\n").unwrap();
+    assert_eq!(vm.pstack(), vec![7]);
+
+    let header = vm.latest();
+    assert_eq!(vm.read_u8(header + 4), HIDDEN);
+    assert_eq!(vm.find(""), None);
+    vm.run_test_source("t.fs", "0 0 find\n").unwrap();
+    assert_eq!(vm.pstack(), vec![7, 0]);
+}
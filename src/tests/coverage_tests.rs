@@ -0,0 +1,57 @@
+use super::word_tests::create_vm;
+
+#[test]
+fn test_coverage() {
+    let mut vm = create_vm();
+    vm.colon_def("abs", &["dup", "0<", "0branch", "8", "negate", "exit"]);
+    vm.colon_def("never", &["dup", "exit"]);
+    let positive = vm.colon_def("test_positive", &["lit", "5", "abs", "bye"]);
+    let negative = vm.colon_def("test_negative", &["lit", "-5", "abs", "bye"]);
+
+    vm.start_coverage();
+    vm.run_word(positive as usize);
+    assert_eq!(vm.pstack(), vec![5]);
+    vm.pop_i32();
+
+    let report = vm.coverage_report();
+    let abs = report.words.iter().find(|w| w.name == "abs").unwrap();
+    assert_eq!((abs.cells, abs.executed), (5, 4));
+    let unexecuted: Vec<_> = report
+        .unexecuted_words()
+        .iter()
+        .map(|w| w.name.as_str())
+        .collect();
+    assert!(unexecuted.contains(&"never"));
+    assert!(unexecuted.contains(&"test_negative"));
+    assert!(!unexecuted.contains(&"abs"));
+
+    let branch = report.branches.iter().find(|b| b.word == "abs").unwrap();
+    assert_eq!((branch.offset, branch.target_offset), (12, 24));
+    assert!(branch.taken);
+
+    vm.run_word(negative as usize);
+    assert_eq!(vm.pstack(), vec![5]);
+    vm.pop_i32();
+
+    let report = vm.stop_coverage();
+    assert!(!vm.is_covering());
+    let abs = report.words.iter().find(|w| w.name == "abs").unwrap();
+    assert_eq!(abs.executed, 5);
+
+    let text = report.to_text();
+    assert!(text.contains("never executed: never\n"));
+    assert!(!text.contains("never executed: abs\n"));
+
+    let lcov = report.to_lcov();
+    assert!(lcov.starts_with("TN:\nSF:<rust>\n"));
+    assert!(lcov.contains("FNDA:0,never\n"));
+    assert!(lcov.contains("FNDA:1,abs\n"));
+    assert!(lcov.ends_with("end_of_record\n"));
+}
+
+#[test]
+fn test_coverage_off() {
+    let vm = create_vm();
+    assert!(!vm.is_covering());
+    assert_eq!(vm.coverage_report().words.len(), 0);
+}
@@ -0,0 +1,175 @@
+use super::word_tests::create_vm;
+use crate::{Breakpoint, ForthError, StopReason};
+
+#[test]
+fn test_breakpoints() {
+    let mut vm = create_vm();
+
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    let test_debug = vm.colon_def(
+        "test_debug",
+        &["lit", "3", "square", "lit", "4", "square", "+", "bye"],
+    );
+
+    vm.add_breakpoint(Breakpoint::Word("square".to_string()))
+        .unwrap();
+    assert_eq!(
+        vm.add_breakpoint(Breakpoint::Word("nope".to_string())),
+        Err(ForthError::UndefinedWord("nope".to_string()))
+    );
+
+    let event = vm.debug_word(test_debug as usize).unwrap();
+    assert_eq!(event.reason, StopReason::Breakpoint);
+    assert_eq!(event.name, "square");
+    assert_eq!(event.pstack, vec![3]);
+
+    let event = vm.debug_continue().unwrap();
+    assert_eq!(event.reason, StopReason::Breakpoint);
+    assert_eq!(event.name, "square");
+    assert_eq!(event.pstack, vec![9, 4]);
+
+    let event = vm.debug_continue().unwrap();
+    assert_eq!(event.reason, StopReason::Finished);
+    assert_eq!(event.pstack, vec![25]);
+    assert_eq!(vm.debug_continue(), Err(ForthError::NotPaused));
+
+    // break on a cell of the colon definition: the second "square"
+    vm.pop_i32();
+    vm.clear_breakpoints();
+    let ic = vm.cfa(test_debug) + 4 * 6;
+    vm.add_breakpoint(Breakpoint::Address(ic)).unwrap();
+    let event = vm.debug_word(test_debug as usize).unwrap();
+    assert_eq!(event.reason, StopReason::Breakpoint);
+    assert_eq!(event.ic, ic);
+    assert_eq!(event.pstack, vec![9, 4]);
+}
+
+#[test]
+fn test_stepping() {
+    let mut vm = create_vm();
+
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    let test_step = vm.colon_def("test_step", &["lit", "3", "square", "1+", "bye"]);
+
+    vm.add_breakpoint(Breakpoint::Word("square".to_string()))
+        .unwrap();
+    let event = vm.debug_word(test_step as usize).unwrap();
+    assert_eq!(event.name, "square");
+
+    let event = vm.step_into().unwrap();
+    assert_eq!(event.reason, StopReason::Step);
+    assert_eq!(event.name, "dup");
+
+    let event = vm.step_over().unwrap();
+    assert_eq!(event.name, "*");
+    assert_eq!(event.pstack, vec![3, 3]);
+
+    let event = vm.step_out().unwrap();
+    assert_eq!(event.name, "1+");
+    assert_eq!(event.pstack, vec![9]);
+
+    let event = vm.step_over().unwrap();
+    assert_eq!(event.name, "bye");
+    assert_eq!(event.pstack, vec![10]);
+
+    // step over a colon definition
+    let event = vm.debug_word(test_step as usize).unwrap();
+    assert_eq!(event.name, "square");
+    let event = vm.step_over().unwrap();
+    assert_eq!(event.name, "1+");
+}
+
+#[test]
+fn test_watchpoint() {
+    let mut vm = create_vm();
+
+    let var = vm.allot(4).unwrap();
+    let var_s = var.to_string();
+    let test_watch = vm.colon_def(
+        "test_watch",
+        &["lit", "1", "lit", &var_s, "!", "lit", "2", "drop", "bye"],
+    );
+
+    vm.add_watchpoint(var);
+    let event = vm.debug_word(test_watch as usize).unwrap();
+    assert_eq!(
+        event.reason,
+        StopReason::Watchpoint {
+            addr: var,
+            old: 0,
+            new: 1
+        }
+    );
+    // stops after the store, before the next word
+    assert_eq!(event.name, "lit");
+
+    vm.remove_watchpoint(var);
+    let event = vm.debug_continue().unwrap();
+    assert_eq!(event.reason, StopReason::Finished);
+}
+
+#[test]
+fn test_watchpoint_at_resume() {
+    let mut vm = create_vm();
+
+    let var = vm.allot(4).unwrap();
+    let var_s = var.to_string();
+    let test_watch = vm.colon_def(
+        "test_watch",
+        &["lit", "1", "lit", &var_s, "!", "lit", "2", "drop", "bye"],
+    );
+    vm.add_breakpoint(Breakpoint::Word("drop".to_string()))
+        .unwrap();
+    vm.add_watchpoint(var);
+    vm.debug_word(test_watch as usize).unwrap();
+    let event = vm.debug_continue().unwrap();
+    assert_eq!(event.reason, StopReason::Breakpoint);
+
+    // changed while stopped, reported at the dispatch the run resumes from
+    vm.write_i32(7, var);
+    let event = vm.debug_continue().unwrap();
+    assert_eq!(
+        event.reason,
+        StopReason::Watchpoint {
+            addr: var,
+            old: 1,
+            new: 7
+        }
+    );
+    assert_eq!(event.name, "drop");
+
+    let event = vm.debug_continue().unwrap();
+    assert_eq!(event.reason, StopReason::Finished);
+}
+
+#[test]
+fn test_debug_evaluate() {
+    let mut vm = create_vm();
+
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    let test_debug = vm.colon_def("test_debug", &["lit", "3", "square", "1+", "bye"]);
+    vm.add_breakpoint(Breakpoint::Word("square".to_string()))
+        .unwrap();
+    vm.debug_word(test_debug as usize).unwrap();
+    let rstack = vm.rstack();
+
+    assert_eq!(vm.debug_evaluate("dup 2 +"), Ok(vec![5]));
+    assert_eq!(vm.pstack(), vec![3]);
+
+    // the failed evaluation leaves the stopped run as it was
+    vm.register("check", |x: i32| {
+        if x > 3 {
+            panic!("too big");
+        }
+        x
+    });
+    assert_eq!(
+        vm.debug_evaluate("7 check"),
+        Err(ForthError::HostPanic("too big".to_string()))
+    );
+    assert!(vm.is_paused());
+    assert_eq!(vm.pstack(), vec![3]);
+    assert_eq!(vm.rstack(), rstack);
+    assert_eq!(vm.debug_continue().unwrap().reason, StopReason::Finished);
+    assert_eq!(vm.pstack(), vec![10]);
+}
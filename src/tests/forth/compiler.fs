@@ -0,0 +1,38 @@
+\ compiler words, run by compiler_tests.rs
+
+testing ' and compile,
+t{ 3 ' dup execute -> 3 3 }t
+: compile-dup ['] dup compile, ; immediate
+: twice compile-dup + ;
+t{ 4 twice -> 8 }t
+
+testing [']
+: get-dup ['] dup ;
+t{ 4 get-dup execute -> 4 4 }t
+
+testing literal and 2literal
+: five [ 2 3 + ] literal ;
+t{ five -> 5 }t
+: pair [ 1 2 ] 2literal ;
+t{ pair -> 1 2 }t
+
+testing [char]
+: char-a [char] a ;
+t{ char-a -> 97 }t
+
+testing postpone
+: if postpone 0branch here @ 0 , ; immediate
+: then here @ over - swap ! ; immediate
+: abs dup 0< if negate then ;
+t{ -5 abs -> 5 }t
+t{ 5 abs -> 5 }t
+: my-literal postpone literal ; immediate
+: six [ 6 ] my-literal ;
+t{ six -> 6 }t
+
+testing recurse
+: fact dup 1 > if dup 1- recurse * then ;
+t{ 5 fact -> 120 }t
+
+testing :noname
+t{ 3 :noname dup + ; execute -> 6 }t
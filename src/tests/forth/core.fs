@@ -0,0 +1,24 @@
+\ core words, run by tester_tests.rs
+
+testing arithmetic
+t{ 1 2 + -> 3 }t
+t{ 7 3 - -> 4 }t
+t{ 6 7 * -> 42 }t
+t{ 7 3 mod -> 1 }t
+t{ 5 negate -> -5 }t
+t{ 3 5 max -> 5 }t
+
+testing stack words
+t{ -> }t
+t{ 1 2 swap -> 2 1 }t
+t{ 1 2 over -> 1 2 1 }t
+t{ 1 2 3 rot -> 2 3 1 }t
+t{ 1 2 nip -> 2 }t
+t{ 1 2 tuck -> 2 1 2 }t
+
+testing colon definitions
+: sq dup * ;
+t{ 5 sq -> 25 }t
+: sum-of-squares
+    sq swap sq + ;
+t{ 3 4 sum-of-squares -> 25 }t
@@ -0,0 +1,39 @@
+\ fused sequences, run by optimizer_tests.rs with and without the optimizer
+
+testing lit +, lit -
+: plus5 5 + ;
+: minus3 3 - ;
+: minus-min -2147483648 - ;
+t{ 10 plus5 -> 15 }t
+t{ 10 minus3 -> 7 }t
+t{ -1 plus5 minus3 -> 1 }t
+t{ 1 minus-min -> -2147483647 }t
+
+testing lit @
+: base@ [ base ] literal @ ;
+t{ base@ -> 10 }t
+
+testing swap drop, over over, r> drop
+: my-nip swap drop ;
+: my-2dup over over ;
+: r-drop >r >r r> drop r> ;
+t{ 1 2 my-nip -> 2 }t
+t{ 1 2 my-2dup -> 1 2 1 2 }t
+t{ 1 2 3 r-drop -> 1 3 }t
+
+testing branch targets
+: if postpone 0branch here @ 0 , ; immediate
+: then here @ over - swap ! ; immediate
+: abs+1 dup 0< if negate then 1 + ;
+: into-fused dup 0< if 2 then + ;
+t{ -5 abs+1 -> 6 }t
+t{ 5 abs+1 -> 6 }t
+t{ 1 -1 into-fused -> 1 1 }t
+t{ 1 into-fused -> 2 }t
+
+testing tail calls
+: countdown dup if 1- recurse then ;
+: tail-dup dup ;
+: calls-tail 1 + tail-dup ;
+t{ 10 countdown -> 0 }t
+t{ 1 calls-tail -> 2 2 }t
@@ -0,0 +1,20 @@
+\ input model words, run by source_tests.rs
+: str [char] ) parse ;
+: str2 [char] ] parse ;
+
+testing parse and parse-name
+t{ str abc) swap drop -> 3 }t
+t{ str ) swap drop -> 0 }t
+t{ parse-name   hello swap drop -> 5 }t
+
+testing source and >in
+t{ source swap drop 0> -> 1 }t
+t{ >in @ 0> -> 1 }t
+t{ source-id -> 0 }t
+
+testing evaluate
+t{ str 1 2 +) evaluate -> 3 }t
+t{ str source-id) evaluate -> -1 }t
+t{ str refill) evaluate -> 0 }t
+t{ str2 1 str 2 3 +) evaluate 10 *] evaluate -> 1 50 }t
+t{ str : sq dup * ;) evaluate 4 sq -> 16 }t
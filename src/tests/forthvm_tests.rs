@@ -7,10 +7,12 @@ use toyvm::opcode;
 fn test_input_buf() {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
     };
     let mut vm = ForthVM::from_config(conf);
     let s = "testing input buffer!\n".to_string();
@@ -28,10 +30,12 @@ fn test_input_buf() {
 fn test_write_previous_idx() {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
     };
     let mut vm = ForthVM::from_config(conf);
 
@@ -49,10 +53,12 @@ fn test_write_previous_idx() {
 fn test_write_name_ex() {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
     };
     let mut vm = ForthVM::from_config(conf);
 
@@ -80,10 +86,12 @@ fn test_write_name_ex() {
 fn test_write_col_def() {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
     };
     let mut vm = ForthVM::from_config(conf);
 
@@ -108,10 +116,12 @@ fn test_write_col_def() {
 fn test_builtin() {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
     };
     let mut vm = ForthVM::from_config(conf);
     let state = STATE.to_ne_bytes();
@@ -148,10 +158,12 @@ fn test_builtin() {
 fn test_find() {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
     };
     let mut vm = ForthVM::from_config(conf);
     let state = STATE.to_ne_bytes();
@@ -186,10 +198,12 @@ fn test_find() {
 fn test_colon_def() {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
     };
     let mut vm = ForthVM::from_config(conf);
 
@@ -215,3 +229,31 @@ fn test_colon_def() {
 
     // vm.add_col_word_ex("+", )
 }
+
+#[test]
+fn test_input_pending() {
+    let conf = VmConfig {
+        memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
+        parameter_stack_size_cells: 256,
+        return_stack_size_cells: 256,
+        call_stack_size_cells: 256,
+        locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
+    };
+    let mut vm = ForthVM::from_config(conf);
+    assert!(!vm.input_pending(), "empty buffer");
+
+    vm.fill_input_buffer("  a \\ comment\n");
+    assert!(vm.input_pending());
+
+    while let Some(c) = vm.read_next_char() {
+        if c == b'a' {
+            break;
+        }
+    }
+    assert!(!vm.input_pending(), "only a comment left");
+
+    vm.fill_input_buffer(" \\ comment\n b\n");
+    assert!(vm.input_pending(), "word after comment line");
+}
@@ -0,0 +1,79 @@
+use super::word_tests::create_vm;
+use crate::ForthError;
+
+#[test]
+fn test_allocate_words() {
+    let mut vm = create_vm();
+    vm.evaluate("100 allocate").unwrap();
+    let [addr, ior] = vm.pstack()[..] else {
+        panic!("two results expected");
+    };
+    assert_eq!(ior, 0);
+    assert_eq!(addr % 4, 0);
+
+    vm.evaluate("drop 8 allocate drop").unwrap();
+    let next = vm.pstack()[1];
+    assert!(next > addr);
+
+    // moves past the second block
+    vm.evaluate("swap 4711 over ! 1000 resize").unwrap();
+    let [_, moved, ior] = vm.pstack()[..] else {
+        panic!("three results expected");
+    };
+    assert_eq!(ior, 0);
+    assert!(moved > next);
+    assert_eq!(vm.read_i32(moved), 4711, "contents kept");
+
+    vm.evaluate("drop dup free swap free").unwrap();
+    assert_eq!(vm.pstack(), vec![next, 0, -60]);
+}
+
+#[test]
+fn test_allocate_failure() {
+    let mut vm = create_vm();
+    vm.evaluate("100000 allocate").unwrap();
+    assert_eq!(vm.pstack(), vec![0, -59]);
+    vm.evaluate("2drop 8 allocate drop dup 100000 resize")
+        .unwrap();
+    let [addr, same, ior] = vm.pstack()[..] else {
+        panic!("three results expected");
+    };
+    assert_eq!((same, ior), (addr, -61));
+
+    // sizes are unsigned, none of them fits the heap
+    vm.evaluate("2drop dup -1 resize").unwrap();
+    assert_eq!(vm.pstack(), vec![addr, addr, -61]);
+    vm.evaluate("2drop drop -1 allocate 2147483647 allocate")
+        .unwrap();
+    assert_eq!(vm.pstack(), vec![0, -59, 0, -59]);
+    assert_eq!(
+        vm.allocate(usize::MAX),
+        Err(ForthError::OutOfMemory(usize::MAX))
+    );
+}
+
+#[test]
+fn test_heap_api() {
+    let mut vm = create_vm();
+    let free = vm.heap_free();
+
+    let a = vm.allocate_bytes(b"hello").unwrap();
+    assert_eq!(vm.read_string(a, 5), "hello");
+    let b = vm.allocate(16).unwrap();
+    assert_eq!(vm.heap_free(), free - 8 - 16);
+
+    // grows in place when the next block is free
+    vm.free(b).unwrap();
+    assert_eq!(vm.resize(a, 20), Ok(a));
+    assert_eq!(vm.read_string(a, 5), "hello");
+
+    // freed blocks are reused
+    vm.free(a).unwrap();
+    assert_eq!(vm.allocate(4), Ok(a));
+
+    assert_eq!(vm.free(a + 4), Err(ForthError::InvalidAddress(a + 4)));
+    assert_eq!(
+        vm.allocate(free + 1),
+        Err(ForthError::OutOfMemory(free + 1))
+    );
+}
@@ -0,0 +1,132 @@
+use std::{cell::Cell, rc::Rc};
+
+use super::word_tests::create_vm;
+use crate::{ForthError, TRUE, mmap};
+
+#[test]
+fn test_register() {
+    let mut vm = create_vm();
+
+    vm.register("double", |n: i32| n * 2);
+    vm.register("sum3", |a: i32, b: i32, c: i32| a + b + c);
+    vm.register("divmod", |a: i32, b: i32| (a % b, a / b));
+    vm.register("hello?", |s: String| s == "hello");
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    vm.register("count", move || counter.set(counter.get() + 1));
+
+    let test_double = vm.colon_def("test_double", &["double", "bye"]);
+    let test_sum3 = vm.colon_def("test_sum3", &["sum3", "bye"]);
+    let test_divmod = vm.colon_def("test_divmod", &["divmod", "bye"]);
+    let test_hello = vm.colon_def("test_hello", &["hello?", "bye"]);
+    let test_count = vm.colon_def("test_count", &["count", "count", "bye"]);
+
+    vm.push_i32(21);
+    vm.run_word(test_double as usize);
+    assert_eq!(vm.pop_i32(), 42);
+
+    vm.push_i32(1);
+    vm.push_i32(2);
+    vm.push_i32(3);
+    vm.run_word(test_sum3 as usize);
+    assert_eq!(vm.pop_i32(), 6);
+
+    vm.push_i32(9);
+    vm.push_i32(2);
+    vm.run_word(test_divmod as usize);
+    assert_eq!(vm.pop_i32(), 4, "quotient on top");
+    assert_eq!(vm.pop_i32(), 1, "remainder");
+
+    // run_word stops vm.run at each host call and continues after it
+    vm.run_word(test_count as usize);
+    assert_eq!(calls.get(), 2);
+    assert_eq!(vm.read_i32(mmap::HOST_RESUME as i32), 0);
+
+    let in_stream = vm.read_i32(mmap::IN_STREAM as i32) as usize;
+    vm.write_str(in_stream, "hello");
+    vm.push_i32(in_stream as i32 + 4);
+    vm.push_i32(5);
+    vm.run_word(test_hello as usize);
+    assert_eq!(vm.pop_i32(), TRUE);
+    assert_eq!(vm.pstack_depth(), 0);
+}
+
+#[test]
+fn test_register_native() {
+    let mut vm = create_vm();
+
+    vm.set_user_data(Vec::<i32>::new());
+
+    // ( n -- ) remember n in host data
+    vm.register_native("log", |forth| {
+        let n = forth.pop_i32();
+        forth.user_data_mut::<Vec<i32>>().unwrap().push(n);
+    });
+
+    // ( -- ) compiles a new word from inside forth
+    vm.register_native("make-square", |forth| {
+        forth.colon_def("square", &["dup", "*", "exit"]);
+    });
+
+    let test_native = vm.colon_def(
+        "test_native",
+        &["lit", "7", "log", "lit", "8", "log", "make-square", "bye"],
+    );
+    vm.run_word(test_native as usize);
+
+    assert_eq!(vm.user_data::<Vec<i32>>(), Some(&vec![7, 8]));
+    assert!(vm.user_data::<String>().is_none());
+
+    let test_square = vm.colon_def("test_square", &["square", "bye"]);
+    vm.push_i32(5);
+    vm.run_word(test_square as usize);
+    assert_eq!(vm.pop_i32(), 25);
+}
+
+#[test]
+fn test_call() {
+    let mut vm = create_vm();
+
+    vm.colon_def("square", &["dup", "*", "exit"]);
+
+    assert_eq!(vm.call("square", &[5]), Ok(vec![25]));
+    assert_eq!(vm.call("/mod", &[9, 2]), Ok(vec![1, 4]));
+    assert_eq!(vm.call("2dup", &[1, 2]), Ok(vec![1, 2, 1, 2]));
+    assert_eq!(vm.call("drop", &[]), Err(ForthError::StackUnderflow));
+    assert_eq!(
+        vm.call("no-such-word", &[]),
+        Err(ForthError::UndefinedWord("no-such-word".to_string()))
+    );
+    assert_eq!(vm.pstack_depth(), 0);
+
+    assert_eq!(vm.call_checked("square", &[4], 1), Ok(vec![16]));
+    assert_eq!(
+        vm.call_checked("2dup", &[1, 2], 2),
+        Err(ForthError::StackEffect {
+            expected: 2,
+            actual: 4
+        })
+    );
+    assert_eq!(
+        vm.call_checked("+", &[1, 2], 2).unwrap_err().to_string(),
+        "expected 2 results, got 1"
+    );
+    assert_eq!(vm.pstack_depth(), 0, "results are dropped on a mismatch");
+
+    // values below the arguments are left alone
+    vm.push_i32(42);
+    assert_eq!(vm.call("square", &[3]), Ok(vec![9]));
+    assert_eq!(vm.pop_i32(), 42);
+
+    // forth -> rust -> forth
+    vm.register_native("square-twice", |forth| {
+        let n = forth.pop_i32();
+        let r = forth.call("square", &[n]).unwrap();
+        let r = forth.call("square", &r).unwrap();
+        forth.push_i32(r[0]);
+    });
+    let test_nested = vm.colon_def("test_nested", &["lit", "3", "square-twice", "1+", "bye"]);
+    vm.run_word(test_nested as usize);
+    assert_eq!(vm.pop_i32(), 82);
+}
@@ -0,0 +1,85 @@
+use super::word_tests::create_vm;
+use crate::{ForthError, ForthVM, VmConfig};
+
+fn create_small_vm(max_memory_size_bytes: usize) -> ForthVM {
+    let conf = VmConfig {
+        memory_size_bytes: 0x10000,
+        max_memory_size_bytes,
+        parameter_stack_size_cells: 256,
+        return_stack_size_cells: 256,
+        call_stack_size_cells: 256,
+        locals_stack_size_cells: 256,
+        heap_size_bytes: 0x1000,
+    };
+    let mut vm = ForthVM::from_config(conf);
+    vm.init_dictionary();
+    vm
+}
+
+#[test]
+fn test_memory_grows() {
+    let mut vm = create_vm();
+    let size = vm.memory_size();
+    let start = vm.dict_start();
+    assert!(start < vm.here());
+
+    vm.evaluate("here @ dup 100000 + here ! 4711 over 99996 + !")
+        .unwrap();
+    let buf = vm.pstack()[0];
+    assert!(vm.memory_size() > size);
+    assert!(vm.memory_size() <= vm.memory_limit());
+    assert_eq!(vm.dict_start(), start);
+
+    // the stacks and the dictionary keep working after the move
+    vm.evaluate("drop : test 1 2 + ; test").unwrap();
+    assert_eq!(vm.pstack(), vec![3]);
+    assert_eq!(vm.read_i32(buf + 99996), 4711);
+}
+
+#[test]
+fn test_dictionary_full() {
+    let mut vm = create_small_vm(0);
+    let size = vm.memory_size();
+    assert_eq!(vm.memory_limit(), size);
+
+    let here = vm.here();
+    assert_eq!(
+        vm.evaluate("1 2 here @ 100000 + here !"),
+        Err(ForthError::DictionaryFull)
+    );
+    assert_eq!(vm.here(), here);
+    assert_eq!(vm.pstack(), vec![]);
+    assert_eq!(vm.memory_size(), size);
+
+    // still usable afterwards
+    vm.evaluate(": test 5 ; test").unwrap();
+    assert_eq!(vm.pstack(), vec![5]);
+}
+
+#[test]
+fn test_colon_def_grows() {
+    let mut vm = create_small_vm(0x40000);
+    let size = vm.memory_size();
+    let body = vec!["1+"; 0x4000];
+    let word = vm.colon_def("many", &body) as i32;
+    assert!(vm.memory_size() > size);
+    assert!(vm.here() > word);
+
+    vm.evaluate("0 many").unwrap();
+    assert_eq!(vm.pstack(), vec![0x4000]);
+}
+
+#[test]
+fn test_allot_full() {
+    let mut vm = create_small_vm(0);
+    let here = vm.here();
+    assert_eq!(vm.allot(0x100000), Err(ForthError::DictionaryFull));
+    assert_eq!(
+        vm.set_here(here + 0x100000),
+        Err(ForthError::DictionaryFull)
+    );
+    assert_eq!(vm.here(), here);
+
+    vm.evaluate(": test 5 ; test").unwrap();
+    assert_eq!(vm.pstack(), vec![5]);
+}
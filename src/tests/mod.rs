@@ -1,7 +1,23 @@
 use crate::align;
 
+mod backtrace_tests;
+mod channels_tests;
+mod compiler_tests;
+mod coverage_tests;
+mod debugger_tests;
 mod forthvm_tests;
+mod heap_tests;
+mod host_tests;
+mod memory_tests;
+mod native_tests;
+mod optimizer_tests;
+mod profiler_tests;
+mod source_tests;
+mod tasks_tests;
+mod tester_tests;
+mod trace_tests;
 mod word_tests;
+mod wordlist_tests;
 
 #[test]
 fn test_align() {
@@ -0,0 +1,111 @@
+use super::word_tests::create_vm;
+use crate::{mmap, out_stream_capture, out_stream_take, tcb};
+
+#[test]
+fn test_forth_files_native() {
+    for file in ["core.fs", "compiler.fs", "optimizer.fs"] {
+        let mut vm = create_vm();
+        vm.set_optimize(true);
+        vm.set_tail_calls(true);
+        vm.set_native_compile(true);
+        out_stream_capture();
+
+        let path = format!("{}/src/tests/forth/{file}", env!("CARGO_MANIFEST_DIR"));
+        let failures = vm.run_test_file(&path).unwrap();
+        assert!(failures.is_empty(), "{file}: {}", out_stream_take());
+    }
+}
+
+#[test]
+fn test_native_word() {
+    let mut vm = create_vm();
+    assert!(!vm.is_native_compile());
+    vm.set_native_compile(true);
+
+    vm.evaluate(": sq dup * ; : sum-of-squares sq swap sq + ;")
+        .unwrap();
+    for name in ["sq", "sum-of-squares"] {
+        let cfa = vm.cfa(vm.find(name).unwrap());
+        assert_eq!(vm.read_i32(cfa), cfa + 4, "{name} is a builtin");
+    }
+
+    let rdepth = vm.rstack_depth();
+    vm.evaluate("3 4 sum-of-squares").unwrap();
+    assert_eq!(vm.pstack(), vec![25]);
+    assert_eq!(vm.rstack_depth(), rdepth);
+}
+
+#[test]
+fn test_native_loop() {
+    let mut vm = create_vm();
+    vm.set_native_compile(true);
+    let word = vm.colon_def(
+        "test_loop",
+        &[
+            "1-",      // 0
+            "dup",     // 1
+            "0branch", // 2
+            "12",      // 3 ( 6 - 3 ) * 4
+            "branch",  // 4
+            "-20",     // 5 ( 0 - 5 ) * 4
+            "bye",     // 6
+        ],
+    );
+    let cfa = vm.cfa(word);
+    assert_eq!(vm.read_i32(cfa), cfa + 4);
+
+    vm.push_i32(100);
+    vm.run_word(word as usize);
+    assert_eq!(vm.pstack(), vec![0]);
+}
+
+#[test]
+fn test_native_recurse() {
+    let mut vm = create_vm();
+    vm.set_native_compile(true);
+    vm.evaluate(": if postpone 0branch here @ 0 , ; immediate")
+        .unwrap();
+    vm.evaluate(": then here @ over - swap ! ; immediate")
+        .unwrap();
+    vm.evaluate(": fact dup 1 > if dup 1- recurse * then ;")
+        .unwrap();
+
+    vm.evaluate("6 fact").unwrap();
+    assert_eq!(vm.pstack(), vec![720]);
+}
+
+#[test]
+fn test_return_stack_words_stay_threaded() {
+    let mut vm = create_vm();
+    vm.set_native_compile(true);
+    vm.evaluate(": r-swap >r >r r> r> ;").unwrap();
+    let cfa = vm.cfa(vm.find("r-swap").unwrap());
+    assert_eq!(vm.read_i32(cfa), mmap::DOCOL as i32);
+
+    vm.evaluate("1 2 r-swap").unwrap();
+    assert_eq!(vm.pstack(), vec![2, 1]);
+}
+
+#[test]
+fn test_native_pause() {
+    let mut vm = create_vm();
+    let counter = vm.allot(4).unwrap();
+    let t1 = vm.new_task("t1").unwrap();
+
+    vm.set_native_compile(true);
+    vm.evaluate(&format!(": step 1 {counter} +! pause ;"))
+        .unwrap();
+    let cfa = vm.cfa(vm.find("step").unwrap());
+    assert_eq!(vm.read_i32(cfa), cfa + 4);
+    vm.set_native_compile(false);
+
+    vm.colon_def("job", &["t1", "activate", "step", "branch", "-8"]);
+    let test = vm.colon_def("test_pause", &["job", "step", "step", "step", "bye"]);
+    vm.run_word(test as usize);
+
+    // both tasks took turns
+    assert_eq!(vm.read_i32(counter), 6);
+    assert_eq!(vm.read_i32(t1 + tcb::STATUS), tcb::AWAKE);
+    assert_eq!(vm.current_task(), vm.call("main-task", &[]).unwrap()[0]);
+    assert_eq!(vm.pstack_depth(), 0);
+}
@@ -0,0 +1,172 @@
+use super::tester_tests::assert_forth_tests;
+use super::word_tests::create_vm;
+use crate::{ForthVM, out_stream_capture, out_stream_take};
+
+fn create_optimizing_vm() -> ForthVM {
+    let mut vm = create_vm();
+    vm.set_optimize(true);
+    vm.set_tail_calls(true);
+    vm
+}
+
+#[test]
+fn test_optimizer_fs() {
+    let mut vm = create_optimizing_vm();
+    out_stream_capture();
+
+    let path = format!(
+        "{}/src/tests/forth/optimizer.fs",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let failures = vm.run_test_file(&path).unwrap();
+    assert!(failures.is_empty(), "{}", out_stream_take());
+}
+
+#[test]
+fn test_optimizer_fs_unoptimized() {
+    assert_forth_tests("src/tests/forth/optimizer.fs");
+}
+
+#[test]
+fn test_off_by_default() {
+    let vm = create_vm();
+    assert!(!vm.is_optimizing());
+    assert!(!vm.is_tail_calling());
+}
+
+#[test]
+fn test_fused_body() {
+    let mut vm = create_vm();
+    let here = vm.here();
+    vm.evaluate(": plain 5 + 3 - swap drop over over ;")
+        .unwrap();
+    let plain = vm.here() - here;
+
+    vm.set_optimize(true);
+    let here = vm.here();
+    vm.evaluate(": fused 5 + 3 - swap drop over over ;")
+        .unwrap();
+    let fused = vm.here() - here;
+
+    // 11 cells become 7
+    assert_eq!(plain - fused, 4 * 4);
+
+    let body = vm.cfa(vm.find("fused").unwrap()) + 4;
+    let lit_add = vm.cfa(vm.find("(lit+)").unwrap());
+    assert_eq!(vm.read_i32(body), lit_add);
+    assert_eq!(vm.read_i32(body + 4), 5);
+    assert_eq!(vm.read_i32(body + 12), -3);
+
+    vm.evaluate("0 1 2 plain").unwrap();
+    let expected = vm.pstack();
+    vm.evaluate("2drop 2drop 0 1 2 fused").unwrap();
+    assert_eq!(vm.pstack(), expected);
+}
+
+#[test]
+fn test_colon_def_optimized() {
+    let mut vm = create_optimizing_vm();
+    let word = vm.colon_def("test_inc", &["lit", "1", "+", "swap", "drop", "bye"]);
+    let body = vm.cfa(word) + 4;
+    assert_eq!(vm.here(), body + 4 * 4);
+
+    vm.push_i32(7);
+    vm.push_i32(41);
+    vm.run_word(word as usize);
+    assert_eq!(vm.pstack(), vec![42]);
+}
+
+#[test]
+fn test_branch_offsets_moved() {
+    let mut vm = create_optimizing_vm();
+    let word = vm.colon_def(
+        "test_skip",
+        &[
+            "lit", "1", "+", "0branch", "20", "lit", "2", "-", "bye", "lit", "3", "+", "bye",
+        ],
+    );
+    let zbranch = vm.cfa(word) + 4 + 8;
+    // skips "(lit+) -2 bye" after fusing
+    assert_eq!(vm.read_i32(zbranch + 4), 16);
+
+    vm.push_i32(10);
+    vm.push_i32(-1);
+    vm.run_word(word as usize);
+    assert_eq!(vm.pstack(), vec![13]);
+    vm.pop_i32();
+
+    vm.push_i32(10);
+    vm.push_i32(0);
+    vm.run_word(word as usize);
+    assert_eq!(vm.pstack(), vec![8]);
+}
+
+#[test]
+fn test_redefined_word_not_fused() {
+    let mut vm = create_optimizing_vm();
+    vm.evaluate(": + * ; : times3 3 + ;").unwrap();
+    vm.push_i32(5);
+    vm.evaluate("times3").unwrap();
+    assert_eq!(vm.pstack(), vec![15]);
+}
+
+fn define_if_then(vm: &mut ForthVM) {
+    vm.evaluate(": if postpone 0branch here @ 0 , ; immediate")
+        .unwrap();
+    vm.evaluate(": then here @ over - swap ! ; immediate")
+        .unwrap();
+}
+
+#[test]
+fn test_tail_recursion() {
+    let mut vm = create_optimizing_vm();
+    define_if_then(&mut vm);
+    vm.evaluate(": countdown dup if 1- recurse then ;").unwrap();
+
+    // far deeper than the 256 cells of the return stack
+    let rdepth = vm.rstack_depth();
+    vm.evaluate("100000 countdown").unwrap();
+    assert_eq!(vm.pstack(), vec![0]);
+    assert_eq!(vm.rstack_depth(), rdepth);
+}
+
+#[test]
+fn test_tail_call_body() {
+    let mut vm = create_optimizing_vm();
+    vm.evaluate(": inner 1 ; : outer inner ;").unwrap();
+
+    let tail = vm.cfa(vm.find("(tail)").unwrap());
+    let inner = vm.cfa(vm.find("inner").unwrap());
+    let body = vm.cfa(vm.find("outer").unwrap()) + 4;
+    assert_eq!(vm.read_i32(body), tail);
+    assert_eq!(vm.read_i32(body + 4), inner);
+    assert_eq!(vm.here(), body + 8);
+}
+
+#[test]
+fn test_no_tail_calls() {
+    let mut vm = create_optimizing_vm();
+    let exit = vm.cfa(vm.find("exit").unwrap());
+    let body = |vm: &ForthVM, name: &str| vm.cfa(vm.find(name).unwrap()) + 4;
+
+    // returns the address it was called from
+    vm.evaluate(": caller-ic notail r> dup >r ;").unwrap();
+    let caller_ic = vm.cfa(vm.find("caller-ic").unwrap());
+    vm.evaluate(": direct caller-ic ;").unwrap();
+    // uses the return stack itself
+    vm.evaluate(": keep >r r> caller-ic ;").unwrap();
+    vm.set_tail_calls(false);
+    vm.evaluate(": off direct ;").unwrap();
+    let direct = vm.cfa(vm.find("direct").unwrap());
+
+    let direct_body = body(&vm, "direct");
+    assert_eq!(vm.read_i32(direct_body), caller_ic);
+    assert_eq!(vm.read_i32(direct_body + 4), exit);
+    assert_eq!(vm.read_i32(body(&vm, "keep") + 8), caller_ic);
+    assert_eq!(vm.read_i32(body(&vm, "keep") + 12), exit);
+    assert_eq!(vm.read_i32(body(&vm, "off")), direct);
+    assert_eq!(vm.read_i32(body(&vm, "off") + 4), exit);
+
+    vm.evaluate("direct").unwrap();
+    assert_eq!(vm.pstack(), vec![direct_body + 4]);
+}
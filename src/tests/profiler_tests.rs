@@ -0,0 +1,42 @@
+use super::word_tests::create_vm;
+
+#[test]
+fn test_profile() {
+    let mut vm = create_vm();
+
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    vm.colon_def("quad", &["square", "square", "exit"]);
+    let test_profile = vm.colon_def("test_profile", &["lit", "2", "quad", "quad", "bye"]);
+
+    assert!(vm.profile_report().is_empty());
+    vm.start_profiling();
+    vm.run_word(test_profile as usize);
+    let report = vm.stop_profiling();
+    assert!(!vm.is_profiling());
+
+    assert_eq!(vm.pop_i32(), 65536);
+
+    let entry = |name: &str| report.iter().find(|e| e.name == name).unwrap().clone();
+
+    assert_eq!(entry("quad").calls, 2);
+    assert_eq!(entry("square").calls, 4);
+    assert_eq!(entry("dup").calls, 4);
+    assert_eq!(entry("*").calls, 4);
+    assert_eq!(entry("exit").calls, 6);
+    assert_eq!(entry("test_profile").calls, 1);
+
+    let dup = entry("dup");
+    assert_eq!(dup.inclusive, dup.exclusive, "builtin");
+
+    let square = entry("square");
+    let quad = entry("quad");
+    let test = entry("test_profile");
+    assert!(square.inclusive > square.exclusive);
+    assert!(quad.inclusive > square.inclusive, "quad calls square");
+    assert!(test.inclusive > quad.inclusive);
+
+    // sorted by exclusive instructions
+    for pair in report.windows(2) {
+        assert!(pair[0].exclusive >= pair[1].exclusive);
+    }
+}
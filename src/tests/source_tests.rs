@@ -0,0 +1,157 @@
+use super::tester_tests::assert_forth_tests;
+use super::word_tests::create_vm;
+use crate::source::EVALUATE_BUFFER;
+use crate::{
+    FALSE, ForthError, ForthVM, SourceLocation, TRUE, VmConfig, out_stream_capture, out_stream_take,
+};
+
+#[test]
+fn test_definition_location() {
+    let mut vm = create_vm();
+
+    vm.set_source_location("foo.fs", 12);
+    vm.fill_input_buffer("  : sq dup * ;\n");
+    for _ in 0..4 {
+        vm.call("interpret", &[]).unwrap();
+    }
+
+    let expected = SourceLocation {
+        file: "foo.fs".to_string(),
+        line: 12,
+        column: 5,
+    };
+    assert_eq!(vm.locate("sq"), Some(&expected));
+    assert_eq!(vm.locate("dup"), None);
+    assert_eq!(vm.source_location().column, 14);
+}
+
+#[test]
+fn test_undefined_word_location() {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    vm.set_source_location("foo.fs", 12);
+    vm.fill_input_buffer("  : sq dup * ;\n");
+    for _ in 0..4 {
+        vm.call("interpret", &[]).unwrap();
+    }
+
+    vm.set_source_location("foo.fs", 13);
+    vm.fill_input_buffer("   dupp 2\n");
+    let result = vm.call("interpret", &[]).unwrap();
+    assert!(result.is_empty(), "no number left behind");
+    assert_eq!(out_stream_take(), "\nfoo.fs:13:4: undefined word 'dupp'\n");
+
+    vm.fill_input_buffer("locate sq locate dupp\n");
+    vm.call("interpret", &[]).unwrap();
+    vm.call("interpret", &[]).unwrap();
+    assert_eq!(
+        out_stream_take(),
+        "\nsq is defined at foo.fs:12:5\n\nundefined word 'dupp'\n"
+    );
+}
+
+#[test]
+fn test_location_per_vm() {
+    let mut a = create_vm();
+    let mut b = create_vm();
+    out_stream_capture();
+
+    a.set_source_location("a.fs", 3);
+    b.set_source_location("b.fs", 7);
+    a.fill_input_buffer("  nope\n");
+    b.fill_input_buffer("dup\n");
+    a.call("interpret", &[]).unwrap();
+    b.push_i32(1);
+    b.call("interpret", &[]).unwrap();
+
+    assert_eq!(out_stream_take(), "\na.fs:3:3: undefined word 'nope'\n");
+    assert_eq!(a.source_location().file, "a.fs");
+    let expected = SourceLocation {
+        file: "b.fs".to_string(),
+        line: 7,
+        column: 1,
+    };
+    assert_eq!(b.source_location(), expected);
+}
+
+#[test]
+fn test_source_fs() {
+    assert_forth_tests("src/tests/forth/source.fs");
+}
+
+#[test]
+fn test_evaluate() {
+    let mut vm = create_vm();
+    vm.fill_input_buffer("4 5\n");
+    assert_eq!(vm.call("parse-name", &[]).map(|v| v.len()), Ok(2));
+
+    vm.evaluate("1 2 + \\ comment").unwrap();
+    assert_eq!(vm.pstack(), vec![3]);
+
+    let name = vm.call("parse-name", &[]).unwrap();
+    assert_eq!(vm.read_string(name[0], name[1]), "5");
+}
+
+#[test]
+fn test_evaluate_long_source() {
+    let mut vm = create_vm();
+    vm.fill_input_buffer("4 5\n");
+    let free = vm.heap_free();
+
+    // longer than the 255 bytes of the input buffer
+    let source = format!("0 {}", "1+ ".repeat(200));
+    vm.evaluate(&source).unwrap();
+    assert_eq!(vm.pstack(), vec![200]);
+    assert_eq!(vm.heap_free(), free);
+
+    let name = vm.call("parse-name", &[]).unwrap();
+    assert_eq!(vm.read_string(name[0], name[1]), "4");
+
+    let source = " ".repeat(EVALUATE_BUFFER + 1);
+    assert_eq!(
+        vm.evaluate(&source),
+        Err(ForthError::SourceTooLong(EVALUATE_BUFFER + 1))
+    );
+}
+
+#[test]
+fn test_evaluate_without_heap() {
+    let conf = VmConfig {
+        memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0,
+        parameter_stack_size_cells: 256,
+        return_stack_size_cells: 256,
+        call_stack_size_cells: 256,
+        locals_stack_size_cells: 256,
+        heap_size_bytes: 0,
+    };
+    let mut vm = ForthVM::from_config(conf);
+    vm.init_dictionary();
+
+    // nested calls share the buffer
+    vm.register_native("inner", |forth| {
+        forth.evaluate("2 3 +").unwrap();
+    });
+    vm.evaluate("1 inner 4").unwrap();
+    assert_eq!(vm.pstack(), vec![1, 5, 4]);
+}
+
+#[test]
+fn test_save_restore_input() {
+    let mut vm = create_vm();
+    vm.fill_input_buffer("first second\n");
+
+    let saved = vm.call("save-input", &[]).unwrap();
+    assert_eq!(saved.len(), 5);
+    assert_eq!(saved[4], 4);
+
+    let name = vm.call("parse-name", &[]).unwrap();
+    assert_eq!(vm.read_string(name[0], name[1]), "first");
+    assert_eq!(vm.call("restore-input", &saved), Ok(vec![FALSE]));
+
+    let name = vm.call("parse-name", &[]).unwrap();
+    assert_eq!(vm.read_string(name[0], name[1]), "first");
+
+    assert_eq!(vm.call("restore-input", &[7, 1]), Ok(vec![TRUE]));
+}
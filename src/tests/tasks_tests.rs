@@ -0,0 +1,104 @@
+use super::word_tests::create_vm;
+use crate::{RunStatus, tcb};
+
+#[test]
+fn test_tasks() {
+    let mut vm = create_vm();
+
+    let counter = vm.allot(4).unwrap();
+    let counter_s = counter.to_string();
+    let t1 = vm.new_task("t1").unwrap();
+    assert_eq!(vm.read_i32(t1 + tcb::STATUS), tcb::ASLEEP);
+
+    vm.colon_def(
+        "job",
+        &[
+            "t1", "activate", "lit", "1", "lit", &counter_s, "+!", "pause", "branch", "-28",
+        ],
+    );
+    let test_tasks = vm.colon_def("test_tasks", &["job", "pause", "pause", "pause", "bye"]);
+
+    vm.run_word(test_tasks as usize);
+
+    assert_eq!(vm.read_i32(counter), 3);
+    assert_eq!(vm.read_i32(t1 + tcb::STATUS), tcb::AWAKE);
+    assert_eq!(vm.current_task(), vm.call("main-task", &[]).unwrap()[0]);
+    assert_eq!(vm.pstack_depth(), 0);
+
+    // a stopped task no longer runs
+    vm.write_i32(tcb::ASLEEP, t1 + tcb::STATUS);
+    let test_pause = vm.colon_def("test_pause", &["pause", "pause", "bye"]);
+    vm.run_word(test_pause as usize);
+    assert_eq!(vm.read_i32(counter), 3);
+
+    // wake it up again
+    let test_wake = vm.colon_def("test_wake", &["t1", "wake", "pause", "bye"]);
+    vm.run_word(test_wake as usize);
+    assert_eq!(vm.read_i32(counter), 4);
+}
+
+#[test]
+fn test_task_done() {
+    let mut vm = create_vm();
+
+    let counter = vm.allot(4).unwrap();
+    let counter_s = counter.to_string();
+    let t1 = vm.new_task("t1").unwrap();
+
+    // runs once, then the task stops itself by returning
+    vm.colon_def(
+        "once",
+        &[
+            "t1", "activate", "lit", "1", "lit", &counter_s, "+!", "exit",
+        ],
+    );
+    let test_once = vm.colon_def("test_once", &["once", "pause", "pause", "pause", "bye"]);
+    vm.run_word(test_once as usize);
+
+    assert_eq!(vm.read_i32(counter), 1);
+    assert_eq!(vm.read_i32(t1 + tcb::STATUS), tcb::ASLEEP);
+}
+
+#[test]
+fn test_wake_before_activate() {
+    let mut vm = create_vm();
+    let t1 = vm.new_task("t1").unwrap();
+
+    // the task has no code yet and goes back to sleep
+    let test_wake = vm.colon_def("test_wake", &["t1", "wake", "pause", "pause", "bye"]);
+    vm.run_word(test_wake as usize);
+    assert_eq!(vm.read_i32(t1 + tcb::STATUS), tcb::ASLEEP);
+    assert_eq!(vm.current_task(), vm.call("main-task", &[]).unwrap()[0]);
+}
+
+#[test]
+fn test_abort_in_task() {
+    let mut vm = create_vm();
+
+    let counter = vm.allot(4).unwrap();
+    let counter_s = counter.to_string();
+    let t1 = vm.new_task("t1").unwrap();
+    let main = vm.current_task();
+
+    // the task never pauses
+    vm.colon_def(
+        "spin",
+        &[
+            "t1", "activate", "lit", "1", "lit", &counter_s, "+!", "branch", "-24",
+        ],
+    );
+    let test_spin = vm.colon_def("test_spin", &["spin", "pause", "bye"]);
+    let status = vm.run_word_with_fuel(test_spin as usize, 100).unwrap();
+    assert_eq!(status, RunStatus::OutOfFuel);
+    assert_eq!(vm.current_task(), t1);
+
+    vm.abort();
+    assert_eq!(vm.current_task(), main);
+    assert_eq!(vm.read_i32(t1 + tcb::STATUS), tcb::ASLEEP);
+    assert_eq!(vm.pstack_depth(), 0);
+
+    let count = vm.read_i32(counter);
+    let test_pause = vm.colon_def("test_pause", &["pause", "pause", "bye"]);
+    vm.run_word(test_pause as usize);
+    assert_eq!(vm.read_i32(counter), count);
+}
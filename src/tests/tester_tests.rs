@@ -0,0 +1,59 @@
+use super::word_tests::create_vm;
+use crate::{ForthError, out_stream_capture, out_stream_take};
+
+/// runs a forth test file, every failed `t{ -> }t` fails the rust test
+pub(super) fn assert_forth_tests(path: &str) {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    let path = format!("{}/{path}", env!("CARGO_MANIFEST_DIR"));
+    let failures = vm.run_test_file(&path).unwrap();
+    let report: Vec<String> = failures.iter().map(|f| f.to_string()).collect();
+    assert!(failures.is_empty(), "{}", report.join("\n"));
+}
+
+#[test]
+fn test_core_fs() {
+    assert_forth_tests("src/tests/forth/core.fs");
+}
+
+#[test]
+fn test_failures() {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    let source = "testing failures\nt{ 1 2 + -> 4 }t\n  t{ 1 2 -> 1 }t\nt{ 1 -> 1 }t\n";
+    let failures = vm.run_test_source("fail.fs", source).unwrap();
+    assert_eq!(failures.len(), 2);
+
+    assert_eq!(
+        failures[0].to_string(),
+        "fail.fs:2:1: incorrect result, expected [4] got [3]: t{ 1 2 + -> 4 }t (testing failures)"
+    );
+    assert_eq!(failures[1].location.line, 3);
+    assert_eq!(failures[1].location.column, 3);
+    assert_eq!(
+        failures[1].message,
+        "wrong number of results, expected [1] got [1, 2]"
+    );
+    assert!(out_stream_take().contains("fail.fs:3:3: wrong number of results"));
+    assert!(vm.pstack().is_empty());
+}
+
+#[test]
+fn test_error_count() {
+    let mut vm = create_vm();
+    out_stream_capture();
+
+    vm.run_test_source("count.fs", "t{ 1 -> 2 }t t{ 3 -> 3 }t #errors")
+        .unwrap();
+    assert_eq!(vm.pstack(), vec![1]);
+    assert!(vm.test_failures().is_empty(), "taken by run_test_source");
+}
+
+#[test]
+fn test_missing_file() {
+    let mut vm = create_vm();
+    let result = vm.run_test_file("no/such/file.fs");
+    assert!(matches!(result, Err(ForthError::Io(_))));
+}
@@ -0,0 +1,89 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::word_tests::create_vm;
+
+#[test]
+fn test_trace() {
+    let mut vm = create_vm();
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let hook_log = log.clone();
+    vm.set_trace_hook(move |event| {
+        hook_log
+            .borrow_mut()
+            .push((event.name.to_string(), event.pstack.to_vec()));
+    });
+
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    let test_trace = vm.colon_def(
+        "test_trace",
+        &[
+            "lit",
+            "1",
+            "trace-on",
+            "lit",
+            "3",
+            "square",
+            "trace-off",
+            "drop",
+            "bye",
+        ],
+    );
+
+    vm.run_word(test_trace as usize);
+    assert!(!vm.is_tracing());
+
+    let expected = vec![
+        ("lit".to_string(), vec![1]),
+        ("square".to_string(), vec![1, 3]),
+        ("dup".to_string(), vec![1, 3]),
+        ("*".to_string(), vec![1, 3, 3]),
+        ("exit".to_string(), vec![1, 9]),
+        ("trace-off".to_string(), vec![1, 9]),
+    ];
+    assert_eq!(*log.borrow(), expected);
+}
+
+#[test]
+fn test_trace_runtime_words() {
+    let mut vm = create_vm();
+
+    vm.fill_input_buffer(": cube dup dup * * ; ");
+    let test_compile = vm.colon_def(
+        "test_compile",
+        &[
+            "interpret",
+            "interpret",
+            "interpret",
+            "interpret",
+            "interpret",
+            "interpret",
+            "bye",
+        ],
+    );
+    vm.run_word(test_compile as usize);
+
+    let xt = vm.cfa(vm.find("cube").unwrap());
+    assert_eq!(vm.xt_name(xt), "cube");
+
+    // words compiled at runtime show up in the trace by name
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let hook_log = log.clone();
+    vm.set_trace_hook(move |event| {
+        hook_log
+            .borrow_mut()
+            .push((event.name.to_string(), event.pstack.to_vec()));
+    });
+    vm.set_tracing(true);
+    assert_eq!(vm.call("cube", &[2]), Ok(vec![8]));
+    vm.set_tracing(false);
+
+    let expected = vec![
+        ("dup".to_string(), vec![2]),
+        ("dup".to_string(), vec![2, 2]),
+        ("*".to_string(), vec![2, 2, 2]),
+        ("*".to_string(), vec![2, 4]),
+        ("exit".to_string(), vec![8]),
+    ];
+    assert_eq!(log.borrow()[..expected.len()], expected);
+}
@@ -1,14 +1,17 @@
 use crate::{
-    COMPILING, FALSE, ForthVM, HIDDEN, IMMEDIATE, INTERPRETING, LEN_MASK, TRUE, VmConfig, mmap,
+    COMPILING, FALSE, ForthError, ForthVM, HIDDEN, IMMEDIATE, INTERPRETING, LEN_MASK, RunStatus,
+    TRUE, VmConfig, mmap,
 };
 
-fn create_vm() -> ForthVM {
+pub(super) fn create_vm() -> ForthVM {
     let conf = VmConfig {
         memory_size_bytes: 0x10000,
+        max_memory_size_bytes: 0x100000,
         parameter_stack_size_cells: 256,
         return_stack_size_cells: 256,
         call_stack_size_cells: 256,
         locals_stack_size_cells: 256,
+        heap_size_bytes: 0x1000,
     };
     let mut vm = ForthVM::from_config(conf);
     vm.init_dictionary();
@@ -808,7 +811,8 @@ fn test_find() {
 fn test_tick() {
     let mut vm = create_vm();
 
-    let test_tick = vm.colon_def("test_tick", &["'", "lit", "bye"]);
+    vm.fill_input_buffer("lit\n");
+    let test_tick = vm.colon_def("test_tick", &["'", "bye"]);
 
     vm.run_word(test_tick as usize);
     let lit_idx = vm.find("lit").unwrap();
@@ -974,6 +978,75 @@ fn test_number() {
     assert_eq!(n, 0, "number 12ab result");
 }
 
+#[test]
+fn test_number_prefixes() {
+    let mut vm = create_vm();
+
+    vm.fill_input_buffer(
+        "$ff #10 %1010 'a' $-10 -2147483648 2147483648 $ffffffff \
+         4294967296 -2147483649 $ $fg #ff ",
+    );
+    let test_number = vm.colon_def("test_number", &["word", "number", "bye"]);
+
+    let expected = [
+        (255, 0),
+        (10, 0),
+        (10, 0),
+        (97, 0),
+        (-16, 0),
+        (i32::MIN, 0),
+        (i32::MIN, 0),
+        (-1, 0),
+        (0, 1),
+        (0, 1),
+        (0, 1),
+        (0, 1),
+        (0, 2),
+    ];
+    for (n, f) in expected {
+        vm.run_word(test_number as usize);
+        assert_eq!((vm.pop_i32(), vm.pop_i32()), (f, n));
+    }
+
+    vm.set_base(16);
+    vm.fill_input_buffer("#10 ");
+    vm.run_word(test_number as usize);
+    assert_eq!((vm.pop_i32(), vm.pop_i32()), (0, 10), "prefix ignores base");
+}
+
+#[test]
+fn test_to_number() {
+    let mut vm = create_vm();
+
+    vm.fill_input_buffer("123abc ");
+    let test_to_number = vm.colon_def(
+        "test_to_number",
+        &["lit", "1", "lit", "0", "word", ">number", "bye"],
+    );
+    vm.run_word(test_to_number as usize);
+
+    let len = vm.pop_i32();
+    let addr = vm.pop_i32();
+    let hi = vm.pop_i32();
+    let lo = vm.pop_i32();
+    assert_eq!((lo, hi, len), (1123, 0, 3));
+    assert_eq!(vm.read_u8(addr), b'a');
+}
+
+#[test]
+fn test_colon_def_number_prefixes() {
+    let mut vm = create_vm();
+    let test_lit = vm.colon_def("test_lit", &["lit", "$ff", "lit", "'a'", "bye"]);
+    vm.run_word(test_lit as usize);
+    assert_eq!(vm.pstack(), vec![255, 97]);
+
+    // numbers without prefix are decimal whatever the base
+    vm.set_base(16);
+    let test_dec = vm.colon_def("test_dec", &["lit", "10", "bye"]);
+    vm.run_word(test_dec as usize);
+    assert_eq!(vm.pstack(), vec![255, 97, 10]);
+}
+
 #[test]
 fn test_execute() {
     let mut vm = create_vm();
@@ -1149,3 +1222,82 @@ fn test_quit() {
 
     assert_eq!(result, 64);
 }
+
+#[test]
+fn test_run_with_fuel() {
+    let mut vm = create_vm();
+
+    let test_done = vm.colon_def("test_done", &["lit", "5", "bye"]);
+    let status = vm.run_word_with_fuel(test_done as usize, 10).unwrap();
+    assert_eq!(status, RunStatus::Done);
+    assert_eq!(vm.pop_i32(), 5);
+
+    // branch back onto itself forever
+    let test_loop = vm.colon_def("test_loop", &["lit", "1", "drop", "branch", "-16"]);
+    let status = vm.run_word_with_fuel(test_loop as usize, 100).unwrap();
+    assert_eq!(status, RunStatus::OutOfFuel);
+    assert!(vm.is_paused());
+
+    let status = vm.resume(100).unwrap();
+    assert_eq!(status, RunStatus::OutOfFuel);
+
+    vm.abort();
+    assert!(!vm.is_paused());
+    assert_eq!(vm.pstack_depth(), 0);
+    assert_eq!(vm.rstack_depth(), 0);
+}
+
+#[test]
+fn test_interrupt() {
+    let mut vm = create_vm();
+
+    let test_loop = vm.colon_def("test_loop", &["lit", "1", "drop", "branch", "-16"]);
+    let handle = vm.interrupt_handle();
+
+    let stopper = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        handle.interrupt();
+    });
+
+    let result = vm.try_run_word(test_loop as usize);
+    stopper.join().unwrap();
+
+    assert_eq!(result, Err(ForthError::Interrupted));
+    assert_eq!(vm.pstack_depth(), 0);
+    assert_eq!(vm.rstack_depth(), 0);
+    assert!(
+        !vm.interrupt_handle().is_interrupted(),
+        "interrupt consumed"
+    );
+
+    // run_word checks the flag while a handle is held
+    let handle = vm.interrupt_handle();
+    let stopper = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        handle.interrupt();
+    });
+    vm.run_word(test_loop as usize);
+    stopper.join().unwrap();
+    assert_eq!(vm.pstack_depth(), 0);
+    assert!(!vm.interrupt_handle().is_interrupted());
+
+    // interrupting while compiling drops the unfinished word
+    vm.fill_input_buffer(": unfinished dup ");
+    let test_compile = vm.colon_def(
+        "test_compile",
+        &["interpret", "interpret", "interpret", "bye"],
+    );
+    let latest = vm.latest();
+    let here = vm.here();
+
+    vm.run_word(test_compile as usize);
+    assert!(vm.is_compiling());
+    assert_ne!(vm.latest(), latest);
+
+    vm.interrupt_handle().interrupt();
+    let result = vm.try_run_word(test_loop as usize);
+    assert_eq!(result, Err(ForthError::Interrupted));
+    assert!(vm.is_interpreting());
+    assert_eq!(vm.latest(), latest);
+    assert_eq!(vm.here(), here);
+}
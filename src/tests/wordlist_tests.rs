@@ -0,0 +1,92 @@
+use super::word_tests::create_vm;
+use crate::{COMPILE_ONLY, HIDDEN, IMMEDIATE, WordKind, out_stream_capture, out_stream_take};
+
+#[test]
+fn test_words_api() {
+    let mut vm = create_vm();
+    vm.register_native("probe", |_| {});
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    let end = vm.here();
+
+    let words: Vec<_> = vm.words().collect();
+    assert_eq!(words.len(), vm.words().count());
+    assert_eq!(words[0].header_addr, vm.latest());
+
+    let square = words.iter().find(|w| w.name == "square").unwrap();
+    assert_eq!(square.kind, WordKind::Colon);
+    assert_eq!(square.cfa, vm.cfa(square.header_addr));
+    assert_eq!(square.size, end - square.header_addr);
+
+    let kind = |name: &str| words.iter().find(|w| w.name == name).unwrap().kind;
+    assert_eq!(kind("probe"), WordKind::Host);
+    assert_eq!(kind("dup"), WordKind::Builtin);
+    assert_eq!(kind("key"), WordKind::Builtin);
+
+    let semicolon = words.iter().find(|w| w.name == ";").unwrap();
+    assert_eq!(semicolon.flags & IMMEDIATE, IMMEDIATE);
+    assert_eq!(semicolon.flags & HIDDEN, 0);
+    assert!(words.iter().all(|w| w.size > 0));
+}
+
+#[test]
+fn test_data_word() {
+    let mut vm = create_vm();
+    // a header made by create without a code field
+    vm.evaluate("word buf create 4711 ,").unwrap();
+    let buf = vm.words().next().unwrap();
+    assert_eq!(buf.name, "buf");
+    assert_eq!(buf.kind, WordKind::Data);
+    assert_eq!(vm.read_i32(buf.cfa), 4711);
+    assert_eq!(buf.size, buf.cfa + 4 - buf.header_addr);
+}
+
+#[test]
+fn test_name_words() {
+    let mut vm = create_vm();
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    out_stream_capture();
+
+    vm.evaluate("latest @ dup .name name>string tell").unwrap();
+    assert_eq!(out_stream_take(), "square square");
+
+    vm.evaluate("3 latest @ name>interpret execute").unwrap();
+    assert_eq!(vm.pstack(), vec![9]);
+
+    // no interpretation semantics
+    let header = vm.latest();
+    vm.write_u8(vm.read_u8(header + 4) | COMPILE_ONLY, header + 4);
+    vm.evaluate("latest @ name>interpret").unwrap();
+    assert_eq!(vm.pstack(), vec![9, 0]);
+}
+
+#[test]
+fn test_words_listing() {
+    let mut vm = create_vm();
+    vm.colon_def("square", &["dup", "*", "exit"]);
+    out_stream_capture();
+    vm.evaluate("words").unwrap();
+    let listing = out_stream_take();
+    assert!(listing.starts_with("square "));
+    assert!(listing.ends_with('\n'));
+    assert!(listing.contains(" dup "));
+
+    // hidden words are left out
+    vm.evaluate("hide square words").unwrap();
+    assert!(!out_stream_take().contains("square"));
+}
+
+#[test]
+fn test_traverse_wordlist() {
+    let mut vm = create_vm();
+    let visible = vm.words().filter(|w| w.flags & HIDDEN == 0).count() as i32;
+
+    vm.evaluate(": counter drop 1+ true ; 0 ' counter forth-wordlist traverse-wordlist")
+        .unwrap();
+    // counter itself is visible after ;
+    assert_eq!(vm.pstack(), vec![visible + 1]);
+
+    // stops at the first false
+    vm.evaluate("drop : first nip false ; 0 ' first forth-wordlist traverse-wordlist")
+        .unwrap();
+    assert_eq!(vm.pstack(), vec![vm.latest()]);
+}
@@ -0,0 +1,121 @@
+use toyvm::opcode;
+
+use crate::{ForthVM, LEN_MASK, MAX_WORD_LEN, forthvm::NEXT, mmap};
+
+/// what is about to run on a NEXT dispatch
+pub struct TraceEvent<'a> {
+    /// name of the word about to be executed
+    pub name: &'a str,
+    /// code field address of that word
+    pub xt: i32,
+    /// address of the cell holding `xt`
+    pub ic: i32,
+    /// parameter stack, bottom first
+    pub pstack: &'a [i32],
+    /// return stack, bottom first
+    pub rstack: &'a [i32],
+}
+
+pub(crate) type TraceHook = Box<dyn FnMut(&TraceEvent)>;
+
+impl ForthVM {
+    pub(crate) fn init_trace_words(&mut self) {
+        let trace = mmap::TRACE.to_ne_bytes();
+        self.builtin(
+            "trace-on",
+            &[
+                opcode::I32_CONST,
+                1,
+                0,
+                0,
+                0,
+                opcode::I32_CONST,
+                trace[0],
+                trace[1],
+                trace[2],
+                trace[3],
+                opcode::I32_STORE,
+                NEXT,
+            ],
+        );
+        self.builtin(
+            "trace-off",
+            &[
+                opcode::I32_CONST,
+                0,
+                0,
+                0,
+                0,
+                opcode::I32_CONST,
+                trace[0],
+                trace[1],
+                trace[2],
+                trace[3],
+                opcode::I32_STORE,
+                NEXT,
+            ],
+        );
+    }
+
+    /// `hook` is called on every NEXT dispatch while tracing is on
+    pub fn set_trace_hook(&mut self, hook: impl FnMut(&TraceEvent) + 'static) {
+        self.trace_hook = Some(Box::new(hook));
+    }
+
+    pub fn clear_trace_hook(&mut self) {
+        self.trace_hook = None;
+    }
+
+    /// same as `trace-on` / `trace-off`
+    pub fn set_tracing(&mut self, on: bool) {
+        self.write_i32(on as i32, mmap::TRACE as i32);
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.read_i32(mmap::TRACE as i32) != 0
+    }
+
+    pub(crate) fn trace_next(&mut self) {
+        if !self.is_tracing() {
+            return;
+        }
+        let Some(mut hook) = self.trace_hook.take() else {
+            return;
+        };
+
+        let ic = self.read_i32(mmap::IC as i32);
+        let xt = self.read_i32(ic);
+        let name = self.xt_name(xt);
+        let pstack = self.pstack();
+        let rstack = self.rstack();
+
+        hook(&TraceEvent {
+            name: &name,
+            xt,
+            ic,
+            pstack: &pstack,
+            rstack: &rstack,
+        });
+
+        self.trace_hook = Some(hook);
+    }
+
+    /// name of the word with code field address `xt`.
+    /// also finds words compiled at runtime, which are not in the vocabulary
+    pub fn xt_name(&self, xt: i32) -> String {
+        if let Some(name) = self.word_name(xt) {
+            return name.to_string();
+        }
+
+        let mut idx = self.latest();
+        while idx != 0 {
+            if self.cfa(idx) == xt {
+                let len = (self.read_u8(idx + 4) & LEN_MASK) as i32;
+                return self.read_string(idx + 5, len.min(MAX_WORD_LEN as i32));
+            }
+            idx = self.read_i32(idx);
+        }
+
+        format!("<{xt}>")
+    }
+}
@@ -0,0 +1,144 @@
+use toyvm::opcode;
+
+use crate::{
+    COMPILE_ONLY, FALSE, ForthVM, HIDDEN, LEN_MASK, MAX_WORD_LEN,
+    forthvm::{HOST_CALL, NEXT},
+    mmap,
+    output_stream::out_stream_write,
+};
+
+/// how a word runs, read from its code field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordKind {
+    /// threaded code run by docol
+    Colon,
+    /// toyvm bytecode, natively compiled colon definitions included
+    Builtin,
+    /// rust closure added with `register_native` or `register`
+    Host,
+    /// no code field, e.g. a header made by `create` followed by data
+    Data,
+}
+
+/// one header of the dictionary, see `ForthVM::words`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordInfo {
+    /// name as stored in the header, at most `MAX_WORD_LEN` bytes
+    pub name: String,
+    pub header_addr: i32,
+    pub cfa: i32,
+    /// `IMMEDIATE`, `HIDDEN` and `COMPILE_ONLY` bits
+    pub flags: u8,
+    pub kind: WordKind,
+    /// bytes up to the next header or `here`
+    pub size: i32,
+}
+
+impl ForthVM {
+    pub(crate) fn init_wordlist_words(&mut self) {
+        // ( -- ) names of the visible words, latest first
+        self.register_native("words", |forth| {
+            let names: Vec<String> = forth
+                .words()
+                .filter(|word| word.flags & HIDDEN == 0)
+                .map(|word| word.name)
+                .collect();
+            out_stream_write(&format!("{}\n", names.join(" ")));
+        });
+        // ( nt -- )
+        self.register_native(".name", |forth| {
+            let nt = forth.pop_i32();
+            out_stream_write(&format!("{} ", forth.header_name(nt)));
+        });
+        // ( nt -- c-addr u )
+        self.register_native("name>string", |forth| {
+            let nt = forth.pop_i32();
+            let len = forth.read_u8(nt + 4) & LEN_MASK;
+            forth.push_i32(nt + 5);
+            forth.push_i32((len as usize).min(MAX_WORD_LEN) as i32);
+        });
+        // ( nt -- xt | 0 ) 0 for compile only words
+        self.register_native("name>interpret", |forth| {
+            let nt = forth.pop_i32();
+            let compile_only = forth.read_u8(nt + 4) & COMPILE_ONLY != 0;
+            forth.push_i32(if compile_only { 0 } else { forth.cfa(nt) });
+        });
+        // ( -- wid ) the only word list, the cell holding its latest header
+        let latest = (mmap::LATEST as i32).to_ne_bytes();
+        self.builtin(
+            "forth-wordlist",
+            &[
+                opcode::I32_CONST,
+                latest[0],
+                latest[1],
+                latest[2],
+                latest[3],
+                NEXT,
+            ],
+        );
+        // ( i*x xt wid -- j*x ) calls xt ( k*x nt -- l*x flag ) for the
+        // visible words, latest first, until it returns false
+        self.register_native("traverse-wordlist", |forth| {
+            let wid = forth.pop_i32();
+            let xt = forth.pop_i32();
+            let mut nt = forth.read_i32(wid);
+            while nt != 0 {
+                // xt may define words, the link is read first
+                let next = forth.read_i32(nt);
+                if forth.read_u8(nt + 4) & HIDDEN == 0 {
+                    forth.push_i32(nt);
+                    if let Err(err) = forth.execute_xt(xt) {
+                        forth.report_error(&format!("traverse-wordlist: {err}"));
+                        return;
+                    }
+                    if forth.pop_i32() == FALSE {
+                        return;
+                    }
+                }
+                nt = next;
+            }
+        });
+    }
+
+    /// the headers of the dictionary, latest first. hidden words are included
+    pub fn words(&self) -> impl Iterator<Item = WordInfo> + '_ {
+        let mut header = self.latest();
+        let mut end = self.here();
+        std::iter::from_fn(move || {
+            if header == 0 {
+                return None;
+            }
+            let info = self.word_info(header, end);
+            end = header;
+            header = self.read_i32(header);
+            Some(info)
+        })
+    }
+
+    fn word_info(&self, header: i32, end: i32) -> WordInfo {
+        let cfa = self.cfa(header);
+        let code = self.read_i32(cfa);
+        let kind = if code == mmap::DOCOL as i32 {
+            WordKind::Colon
+        } else if code != cfa + 4 {
+            WordKind::Data
+        } else if self.read_u8(cfa + 4) == opcode::I32_CONST && self.read_u8(cfa + 9) == HOST_CALL {
+            WordKind::Host
+        } else {
+            WordKind::Builtin
+        };
+        WordInfo {
+            name: self.header_name(header),
+            header_addr: header,
+            cfa,
+            flags: self.read_u8(header + 4) & !LEN_MASK,
+            kind,
+            size: end - header,
+        }
+    }
+
+    fn header_name(&self, header: i32) -> String {
+        let len = self.read_u8(header + 4) & LEN_MASK;
+        self.read_string(header + 5, (len as usize).min(MAX_WORD_LEN) as i32)
+    }
+}